@@ -1,5 +1,8 @@
 pub mod bible;
 pub mod printing;
+pub mod usfm;
+pub mod usx;
+mod test;
 use std::{env, fs, path::Path};
 
 fn main() -> Result<(), String>
@@ -8,7 +11,8 @@ fn main() -> Result<(), String>
         .map(|a| a.to_string())
         .collect();
     
-    if args.len() < 4 { return Err("Expected an input file path, `-o` flag, and an output folder path".into()) }
+    // inputs can be json bible files, single USFM/USX book files, or folders of USFM/USX book files
+    if args.len() < 4 { return Err("Expected an input file or folder path, `-o` flag, and an output folder path".into()) }
 
     let Some(idx) = args.iter().position(|a| a == "-o") else {
         return Err("Expected an output path".into());
@@ -24,19 +28,19 @@ fn main() -> Result<(), String>
 
     let input_paths = args.iter().skip(1).take(idx - 1).into_iter().map(|a| {
         let path = Path::new(a);
-        if path.exists() && path.is_file()
+        if path.exists()
         {
             Ok(path)
         }
         else 
         {
-            Err(format!("File `{}` does not exist or is not a file", a))    
+            Err(format!("Path `{}` does not exist", a))    
         }
     }).collect::<Result<Vec<_>, _>>()?;
     
     for path in input_paths
     {
-        match bible::parse_json_file(path.to_str().unwrap()) 
+        match parse_input(path) 
         {
             Ok(ok) => 
             {
@@ -56,3 +60,28 @@ fn main() -> Result<(), String>
 
     Ok(())
 }
+
+fn parse_input(path: &Path) -> Result<bible::Bible, String>
+{
+    let name = path.file_stem().unwrap().to_str().unwrap().to_owned();
+
+    if path.is_dir()
+    {
+        let book_paths = usfm::get_book_files(path)?;
+        if book_paths.is_empty()
+        {
+            return Err("Folder does not contain any USFM or USX files".into());
+        }
+
+        let book_paths = book_paths.iter().map(|p| p.as_path()).collect::<Vec<_>>();
+        usfm::parse_book_files(&name, &book_paths)
+    }
+    else if usfm::is_usfm_file(path) || usfm::is_usx_file(path)
+    {
+        usfm::parse_book_files(&name, &[path])
+    }
+    else 
+    {
+        bible::parse_json_file(path.to_str().unwrap())
+    }
+}
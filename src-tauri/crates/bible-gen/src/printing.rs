@@ -5,6 +5,7 @@ use crate::bible::Bible;
 lazy_static::lazy_static! 
 {
     static ref REPLACE_REGEX: Regex = Regex::new(r"<FI>(.*?)<Fi>").unwrap();
+    static ref RED_LETTER_REGEX: Regex = Regex::new(r"<FR>(.*?)<Fr>").unwrap();
 }
 
 pub fn convert_bible(bible: &Bible) -> String
@@ -41,6 +42,7 @@ pub fn convert_bible(bible: &Bible) -> String
 fn reformat_text(src: &str) -> String
 {
     let ret = REPLACE_REGEX.replace_all(src, "[$1]").into_owned();
+    let ret = RED_LETTER_REGEX.replace_all(&ret, "{$1}").into_owned();
     ret
 }
 
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests
+{
+    use crate::bible::{Bible, Book};
+    use crate::printing::convert_bible;
+    use crate::usfm::parse_usfm_book;
+    use crate::usx::parse_usx_book;
+
+    fn verse_texts(book: &Book) -> Vec<(u32, u32, String)>
+    {
+        book.chapters.iter().flat_map(|c| {
+            c.verses.iter().map(|v| (c.number.get(), v.number.get(), v.text.clone()))
+        }).collect()
+    }
+
+    fn print_book(book: Book) -> String
+    {
+        convert_bible(&Bible {
+            name: "TEST".into(),
+            description: "".into(),
+            books: vec![book],
+        })
+    }
+
+    #[test]
+    fn test_usfm_structure()
+    {
+        let src = "\\id JHN Test translation\n\
+                   \\h Gospel of John\n\
+                   \\mt1 The Gospel According to John\n\
+                   \\c 1\n\
+                   \\s1 The Word\n\
+                   \\p\n\
+                   \\v 1 In the beginning was the Word,\n\
+                   \\v 2 The same was in the beginning with God.\n\
+                   \\c 2\n\
+                   \\p\n\
+                   \\v 1 And the third day\n\
+                   \\q1 there was a marriage.\n";
+
+        let (code, book) = parse_usfm_book(src).unwrap();
+        assert_eq!(code, "JHN");
+        assert_eq!(book.name, "John");
+        assert_eq!(verse_texts(&book), vec![
+            (1, 1, "In the beginning was the Word,".to_owned()),
+            (1, 2, "The same was in the beginning with God.".to_owned()),
+            (2, 1, "And the third day there was a marriage.".to_owned()),
+        ]);
+
+        assert!(parse_usfm_book("\\c 1\n\\v 1 No book code").is_err());
+        assert!(parse_usfm_book("\\id GEN\n\\v 1 Not in a chapter").is_err());
+    }
+
+    #[test]
+    fn test_usfm_markup()
+    {
+        let src = "\\id MAT\n\
+                   \\c 5\n\
+                   \\v 3 \\wj Blessed \\+add are\\+add* the poor in spirit:\\wj* for theirs \\add is\\add* the kingdom.\n";
+
+        let (_, book) = parse_usfm_book(src).unwrap();
+        assert_eq!(book.chapters[0].verses[0].text, "<FR>Blessed <FI>are<Fi> the poor in spirit:<Fr> for theirs <FI>is<Fi> the kingdom.");
+
+        let printed = print_book(book);
+        assert!(printed.ends_with("Matthew 5:3 {Blessed [are] the poor in spirit:} for theirs [is] the kingdom.\n"));
+    }
+
+    #[test]
+    fn test_usfm_notes_skipped()
+    {
+        let src = "\\id GEN\n\
+                   \\c 1\n\
+                   \\v 1 In the beginning\\f + \\fr 1:1 \\ft Or \\fq when\\fq* God began\\f* God created\\x - \\xo 1:1 \\xt John 1:1\\x* the heaven.\n\
+                   \\v 2 And the \\w earth|strong=\"H0776\"\\w* was without form.\n";
+
+        let (_, book) = parse_usfm_book(src).unwrap();
+        assert_eq!(verse_texts(&book), vec![
+            (1, 1, "In the beginning God created the heaven.".to_owned()),
+            (1, 2, "And the earth<H0776> was without form.".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_usfm_red_letters_across_verses()
+    {
+        let src = "\\id JHN\n\
+                   \\c 14\n\
+                   \\v 1 \\wj Let not your heart be troubled:\n\
+                   \\v 2 In my Father's \\add house\\add* are many mansions.\\wj*\n\
+                   \\v 3 And if I go\n";
+
+        let (_, book) = parse_usfm_book(src).unwrap();
+        assert_eq!(verse_texts(&book), vec![
+            (14, 1, "<FR>Let not your heart be troubled:<Fr>".to_owned()),
+            (14, 2, "<FR>In my Father's <FI>house<Fi> are many mansions.<Fr>".to_owned()),
+            (14, 3, "And if I go".to_owned()),
+        ]);
+
+        let printed = print_book(book);
+        assert!(printed.contains("John 14:1 {Let not your heart be troubled:}\n"));
+        assert!(printed.contains("John 14:2 {In my Father's [house] are many mansions.}\n"));
+    }
+
+    #[test]
+    fn test_usx2_verses()
+    {
+        let src = r#"<?xml version="1.0" encoding="utf-8"?>
+            <usx version="2.5">
+                <book code="JHN" style="id">Test translation</book>
+                <para style="h">Gospel of John</para>
+                <chapter number="3" style="c" />
+                <para style="s1">For God so loved</para>
+                <para style="p">
+                    <verse number="16" style="v" /><char style="wj">For God so loved the world, that he gave his <char style="add">only</char> Son</char><note caller="+" style="f"><char style="ft">Or only begotten</char></note>
+                    <verse number="17" style="v" />For God sent not his Son<note caller="-" style="x"><char style="xt">Luke 19:10</char></note> to condemn the world.
+                </para>
+            </usx>"#;
+
+        let (code, book) = parse_usx_book(src).unwrap();
+        assert_eq!(code, "JHN");
+        assert_eq!(verse_texts(&book), vec![
+            (3, 16, "<FR>For God so loved the world, that he gave his <FI>only<Fi> Son<Fr>".to_owned()),
+            (3, 17, "For God sent not his Son to condemn the world.".to_owned()),
+        ]);
+
+        let printed = print_book(book);
+        assert!(printed.contains("John 3:16 {For God so loved the world, that he gave his [only] Son}\n"));
+    }
+
+    #[test]
+    fn test_usx3_milestones()
+    {
+        let src = r#"<usx version="3.0">
+                <book code="PSA" style="id" />
+                <chapter number="23" style="c" sid="PSA 23" />
+                <para style="d">A Psalm of David.</para>
+                <para style="q1"><verse number="1" style="v" sid="PSA 23:1" />The LORD is my shepherd;<verse eid="PSA 23:1" /></para>
+                <para style="s1">Not part of a verse</para>
+                <para style="q1"><verse number="2" style="v" sid="PSA 23:2" />He maketh me to lie down<verse eid="PSA 23:2" /></para>
+                <para style="b">Between verses</para>
+                <chapter eid="PSA 23" />
+            </usx>"#;
+
+        let (code, book) = parse_usx_book(src).unwrap();
+        assert_eq!(code, "PSA");
+        assert_eq!(book.name, "Psalms");
+        assert_eq!(verse_texts(&book), vec![
+            (23, 1, "The LORD is my shepherd;".to_owned()),
+            (23, 2, "He maketh me to lie down".to_owned()),
+        ]);
+
+        // the words of Christ, with verse milestones inside them
+        let src = r#"<usx version="3.0">
+                <book code="MAT" style="id" />
+                <chapter number="11" style="c" sid="MAT 11" />
+                <para style="p"><verse number="28" style="v" sid="MAT 11:28" /><char style="wj">Come unto me, all ye that labour,<verse eid="MAT 11:28" />
+                <verse number="29" style="v" sid="MAT 11:29" />Take my yoke upon you,</char> and learn of me<verse eid="MAT 11:29" /></para>
+            </usx>"#;
+
+        let (_, book) = parse_usx_book(src).unwrap();
+        assert_eq!(verse_texts(&book), vec![
+            (11, 28, "<FR>Come unto me, all ye that labour,<Fr>".to_owned()),
+            (11, 29, "<FR>Take my yoke upon you,<Fr> and learn of me".to_owned()),
+        ]);
+
+        let printed = print_book(book);
+        assert!(printed.contains("Matthew 11:29 {Take my yoke upon you,} and learn of me\n"));
+    }
+}
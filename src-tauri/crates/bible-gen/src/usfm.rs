@@ -0,0 +1,407 @@
+use std::{fs, num::NonZeroU32, path::{Path, PathBuf}};
+
+use regex::Regex;
+
+use crate::bible::{Bible, Book, Chapter, Verse};
+
+// Converts USFM (https://ubsicap.github.io/usfm/) books into a `Bible`.
+// Italics (`\add`) are written as `<FI>...<Fi>` and the words of Christ (`\wj`) as `<FR>...<Fr>`,
+// the same markup used by the json sources, with any formatting that crosses a verse boundary closed and reopened, so each
+// verse is complete on its own. Strong's numbers (`\w word|strong="H1234"\w*`) are written as `word<H1234>`
+
+lazy_static::lazy_static!
+{
+    static ref MARKER_REGEX: Regex = Regex::new(r"\\(?<marker>\+?[a-z]+[0-9]*)(?<end>\*)?").unwrap();
+    static ref WORD_ATTRIBUTE_REGEX: Regex = Regex::new(r"\|[^\\]*").unwrap();
+    static ref STRONGS_ATTRIBUTE_REGEX: Regex = Regex::new(r#"strong="(?<numbers>[^"]*)""#).unwrap();
+}
+
+pub const USFM_EXTENSIONS: &[&str] = &["usfm", "sfm"];
+pub const USX_EXTENSIONS: &[&str] = &["usx"];
+
+/// USFM book codes, and the names used for them in the app, in canonical order
+pub const BOOK_CODES: &[(&str, &str)] = &[
+    ("GEN", "Genesis"),
+    ("EXO", "Exodus"),
+    ("LEV", "Leviticus"),
+    ("NUM", "Numbers"),
+    ("DEU", "Deuteronomy"),
+    ("JOS", "Joshua"),
+    ("JDG", "Judges"),
+    ("RUT", "Ruth"),
+    ("1SA", "1 Samuel"),
+    ("2SA", "2 Samuel"),
+    ("1KI", "1 Kings"),
+    ("2KI", "2 Kings"),
+    ("1CH", "1 Chronicles"),
+    ("2CH", "2 Chronicles"),
+    ("EZR", "Ezra"),
+    ("NEH", "Nehemiah"),
+    ("EST", "Esther"),
+    ("JOB", "Job"),
+    ("PSA", "Psalms"),
+    ("PRO", "Proverbs"),
+    ("ECC", "Ecclesiastes"),
+    ("SNG", "Song of Solomon"),
+    ("ISA", "Isaiah"),
+    ("JER", "Jeremiah"),
+    ("LAM", "Lamentations"),
+    ("EZK", "Ezekiel"),
+    ("DAN", "Daniel"),
+    ("HOS", "Hosea"),
+    ("JOL", "Joel"),
+    ("AMO", "Amos"),
+    ("OBA", "Obadiah"),
+    ("JON", "Jonah"),
+    ("MIC", "Micah"),
+    ("NAM", "Nahum"),
+    ("HAB", "Habakkuk"),
+    ("ZEP", "Zephaniah"),
+    ("HAG", "Haggai"),
+    ("ZEC", "Zechariah"),
+    ("MAL", "Malachi"),
+    ("MAT", "Matthew"),
+    ("MRK", "Mark"),
+    ("LUK", "Luke"),
+    ("JHN", "John"),
+    ("ACT", "Acts"),
+    ("ROM", "Romans"),
+    ("1CO", "1 Corinthians"),
+    ("2CO", "2 Corinthians"),
+    ("GAL", "Galatians"),
+    ("EPH", "Ephesians"),
+    ("PHP", "Philippians"),
+    ("COL", "Colossians"),
+    ("1TH", "1 Thessalonians"),
+    ("2TH", "2 Thessalonians"),
+    ("1TI", "1 Timothy"),
+    ("2TI", "2 Timothy"),
+    ("TIT", "Titus"),
+    ("PHM", "Philemon"),
+    ("HEB", "Hebrews"),
+    ("JAS", "James"),
+    ("1PE", "1 Peter"),
+    ("2PE", "2 Peter"),
+    ("1JN", "1 John"),
+    ("2JN", "2 John"),
+    ("3JN", "3 John"),
+    ("JUD", "Jude"),
+    ("REV", "Revelation"),
+];
+
+/// Markers whose content, up to the end of the line, is not part of the verse text (headings, titles, etc)
+const LINE_MARKERS: &[&str] = &[
+    "ide", "sts", "rem", "h", "toc", "toca", "mt", "mte", "ms", "mr", "s", "sr", "r", "d", "sp", "cl", "cp", "ca", "va", "vp",
+];
+
+/// Paragraph and poetry markers, which separate the words on either side of them
+const PARAGRAPH_MARKERS: &[&str] = &[
+    "p", "m", "po", "pr", "cls", "pmo", "pm", "pmc", "pmr", "pi", "mi", "nb", "pc", "ph", "b", "q", "qr", "qc", "qa", "qm", "qd", "lh", "li", "lf", "lim",
+];
+
+/// Character markers whose content is skipped until their closing marker (footnotes and cross references)
+const NOTE_MARKERS: &[&str] = &["f", "fe", "x", "ef", "ex"];
+
+pub fn is_usfm_file(path: &Path) -> bool
+{
+    has_extension(path, USFM_EXTENSIONS)
+}
+
+pub fn is_usx_file(path: &Path) -> bool
+{
+    has_extension(path, USX_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool
+{
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+pub fn get_book_name(code: &str) -> Option<&'static str>
+{
+    BOOK_CODES.iter().find(|(c, _)| c.eq_ignore_ascii_case(code)).map(|(_, name)| *name)
+}
+
+fn get_book_order(code: &str) -> usize
+{
+    BOOK_CODES.iter().position(|(c, _)| c.eq_ignore_ascii_case(code)).unwrap_or(BOOK_CODES.len())
+}
+
+/// Builds a bible out of a set of USFM and/or USX book files, named `name`. Books are sorted into canonical order
+pub fn parse_book_files(name: &str, paths: &[&Path]) -> Result<Bible, String>
+{
+    let mut books = paths.iter().map(|path| {
+        let src = match fs::read_to_string(path)
+        {
+            Ok(ok) => ok,
+            Err(err) => return Err(format!("{}: {}", path.display(), err)),
+        };
+
+        let parsed = if is_usx_file(path) { crate::usx::parse_usx_book(&src) } else { parse_usfm_book(&src) };
+        parsed.map_err(|err| format!("{}: {}", path.display(), err))
+    }).collect::<Result<Vec<_>, String>>()?;
+
+    books.sort_by_key(|(code, _)| get_book_order(code));
+
+    Ok(Bible {
+        name: name.into(),
+        description: "".into(),
+        books: books.into_iter().map(|(_, book)| book).collect(),
+    })
+}
+
+/// Parses a single USFM book, returning the book code along with the book
+pub fn parse_usfm_book(src: &str) -> Result<(String, Book), String>
+{
+    let mut builder = BookBuilder::default();
+
+    // headings and other line markers are handled first, as they end with the line
+    let mut body = String::new();
+    for line in src.lines()
+    {
+        let trimmed = line.trim_start();
+        let marker = trimmed.strip_prefix('\\')
+            .and_then(|l| l.split_whitespace().next())
+            .unwrap_or_default();
+        let content = trimmed.get(marker.len() + 1..).unwrap_or_default().trim();
+
+        match marker.trim_end_matches(|c: char| c.is_ascii_digit())
+        {
+            "id" => builder.code = content.split_whitespace().next().map(|c| c.to_ascii_uppercase()),
+            "h" => builder.name = Some(content.to_owned()),
+            base if LINE_MARKERS.contains(&base) => {},
+            _ => {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+    }
+
+    let mut note_depth: u32 = 0;
+    let mut last_end = 0;
+    for captures in MARKER_REGEX.captures_iter(&body)
+    {
+        let whole = captures.get(0).unwrap();
+        let marker = captures.name("marker").unwrap().as_str().trim_start_matches('+');
+        let is_end = captures.name("end").is_some();
+
+        if note_depth == 0
+        {
+            builder.push_text(&body[last_end..whole.start()]);
+        }
+
+        // the space after an opening marker is part of the marker
+        last_end = whole.end();
+        if !is_end && body[last_end..].starts_with(' ')
+        {
+            last_end += 1;
+        }
+
+        if NOTE_MARKERS.contains(&marker)
+        {
+            note_depth = if is_end { note_depth.saturating_sub(1) } else { note_depth + 1 };
+            continue;
+        }
+
+        if note_depth > 0 { continue; }
+
+        match (marker, is_end)
+        {
+            ("c", false) => {
+                let (number, length) = read_number(&body[last_end..])?;
+                last_end += length;
+                builder.start_chapter(number);
+            },
+            ("v", false) => {
+                let (number, length) = read_number(&body[last_end..])?;
+                last_end += length;
+                builder.start_verse(number)?;
+            },
+            ("add", false) => builder.open_format(Format::Italic),
+            ("add", true) => builder.close_format(Format::Italic),
+            ("wj", false) => builder.open_format(Format::Red),
+            ("wj", true) => builder.close_format(Format::Red),
+            (marker, false) if PARAGRAPH_MARKERS.contains(&marker.trim_end_matches(|c: char| c.is_ascii_digit())) => {
+                builder.push_text(" ");
+            },
+            _ => {} // any other character markers are removed, keeping their text
+        }
+    }
+
+    if note_depth == 0
+    {
+        builder.push_text(&body[last_end..]);
+    }
+
+    builder.build()
+}
+
+/// Reads a chapter or verse number, such as `3` or `3-4` (only the first number of a range is kept).
+/// Returns the number, and the length of the text that was read
+fn read_number(src: &str) -> Result<(NonZeroU32, usize), String>
+{
+    let start = src.len() - src.trim_start().len();
+    let end = src[start..].find(char::is_whitespace).map_or(src.len(), |e| e + start);
+    let number = src[start..end].split(['-', ',']).next().unwrap_or_default();
+
+    match number.trim_end_matches(char::is_alphabetic).parse::<u32>().ok().and_then(NonZeroU32::new)
+    {
+        Some(n) => Ok((n, end)),
+        None => Err(format!("Expected a chapter or verse number, found `{}`", &src[start..end])),
+    }
+}
+
+/// The formatting of words, written as `<FI>...<Fi>` for italics and `<FR>...<Fr>` for the words of Christ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format
+{
+    Italic,
+    Red,
+}
+
+impl Format
+{
+    fn open_tag(self) -> &'static str
+    {
+        match self
+        {
+            Format::Italic => "<FI>",
+            Format::Red => "<FR>",
+        }
+    }
+
+    fn close_tag(self) -> &'static str
+    {
+        match self
+        {
+            Format::Italic => "<Fi>",
+            Format::Red => "<Fr>",
+        }
+    }
+}
+
+/// Shared between the USFM and USX parsers, collects the verses of a single book
+#[derive(Debug, Default)]
+pub(crate) struct BookBuilder
+{
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub chapters: Vec<Chapter>,
+    verse: Option<Verse>,
+    /// The formatting that has been opened but not yet closed, innermost last. A span (such as the words of Christ) can cross
+    /// from one verse into the next, so it is closed at the end of each verse, and opened again at the start of the next
+    formats: Vec<Format>,
+    /// The formatting that was still open when the current verse started
+    verse_formats: Vec<Format>,
+}
+
+impl BookBuilder
+{
+    pub fn start_chapter(&mut self, number: NonZeroU32)
+    {
+        self.finish_verse();
+        self.chapters.push(Chapter { number, verses: vec![] });
+    }
+
+    pub fn start_verse(&mut self, number: NonZeroU32) -> Result<(), String>
+    {
+        self.finish_verse();
+        if self.chapters.is_empty()
+        {
+            return Err(format!("Verse {} is not in a chapter", number));
+        }
+
+        self.verse = Some(Verse { number, text: String::new() });
+        self.verse_formats = self.formats.clone();
+        Ok(())
+    }
+
+    pub fn open_format(&mut self, format: Format)
+    {
+        self.formats.push(format);
+        self.push_text(format.open_tag());
+    }
+
+    /// A format that was never opened is ignored
+    pub fn close_format(&mut self, format: Format)
+    {
+        if let Some(i) = self.formats.iter().rposition(|f| *f == format)
+        {
+            self.formats.remove(i);
+            self.push_text(format.close_tag());
+        }
+    }
+
+    /// Text outside of a verse (such as introductions) is ignored
+    pub fn push_text(&mut self, text: &str)
+    {
+        if let Some(verse) = self.verse.as_mut()
+        {
+            // only the Strong's numbers are kept from the `\w word|strong="H1234"\w*` attributes
+            let text = WORD_ATTRIBUTE_REGEX.replace_all(text, |captures: &regex::Captures| {
+                STRONGS_ATTRIBUTE_REGEX.captures(&captures[0])
+                    .map(|c| get_strongs_tags(&c["numbers"]))
+                    .unwrap_or_default()
+            });
+
+            verse.text.push_str(&text);
+        }
+    }
+
+    pub fn finish_verse(&mut self)
+    {
+        let Some(mut verse) = self.verse.take() else {
+            return;
+        };
+
+        let text = verse.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let open = self.verse_formats.iter().map(|f| f.open_tag()).collect::<String>();
+        let close = self.formats.iter().rev().map(|f| f.close_tag()).collect::<String>();
+        verse.text = format!("{}{}{}", open, text, close);
+        if let Some(chapter) = self.chapters.last_mut()
+        {
+            chapter.verses.push(verse);
+        }
+    }
+
+    pub fn build(mut self) -> Result<(String, Book), String>
+    {
+        self.finish_verse();
+
+        let Some(code) = self.code else {
+            return Err("Expected a book code".into());
+        };
+
+        // prefer the app's standard names, as the `\h` headers vary between translations
+        let name = get_book_name(&code)
+            .map(|n| n.to_owned())
+            .or(self.name)
+            .unwrap_or(code.clone());
+
+        Ok((code, Book { name, chapters: self.chapters }))
+    }
+}
+
+/// Converts a `strong` attribute value (`H1234` or `H1234,G5678`) into `<H1234>` tags
+pub(crate) fn get_strongs_tags(numbers: &str) -> String
+{
+    numbers.split(',')
+        .map(|n| n.trim())
+        .filter(|n| !n.is_empty())
+        .map(|n| format!("<{}>", n))
+        .collect()
+}
+
+/// Gets all of the USFM and USX book files in a folder, for bibles that are split into one file per book
+pub fn get_book_files(dir: &Path) -> Result<Vec<PathBuf>, String>
+{
+    let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+    let mut paths = entries.filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && (is_usfm_file(p) || is_usx_file(p)))
+        .collect::<Vec<_>>();
+
+    paths.sort();
+    Ok(paths)
+}
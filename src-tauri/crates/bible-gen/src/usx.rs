@@ -0,0 +1,131 @@
+use minidom::{Element, NSChoice, Node};
+
+use crate::{bible::Book, usfm::{self, BookBuilder, Format}};
+
+// Converts USX (https://ubsicap.github.io/usx/) books into a `Bible`, using the same markup as the USFM parser.
+// Supports both USX 2 (verses end at the next verse) and USX 3 (verses have an `eid` milestone)
+
+const USX_NAMESPACE: &str = "usx";
+
+/// Paragraph styles whose content is not part of the verse text (headings, titles, etc)
+const SKIPPED_PARA_STYLES: &[&str] = &[
+    "ide", "sts", "rem", "h", "toc", "toca", "mt", "mte", "ms", "mr", "s", "sr", "r", "d", "sp", "cl", "cp",
+];
+
+/// Parses a single USX book, returning the book code along with the book
+pub fn parse_usx_book(src: &str) -> Result<(String, Book), String>
+{
+    // minidom requires a namespace, which USX files do not normally have
+    let src = if src.contains("xmlns")
+    {
+        src.to_owned()
+    }
+    else
+    {
+        src.replacen("<usx", &format!("<usx xmlns=\"{}\"", USX_NAMESPACE), 1)
+    };
+
+    let root: Element = src.parse().map_err(|e| format!("Invalid USX xml: {}", e))?;
+    if !root.is("usx", NSChoice::Any)
+    {
+        return Err("Expected a `usx` root element".into());
+    }
+
+    let mut builder = BookBuilder::default();
+    parse_element(&root, &mut builder)?;
+    builder.build()
+}
+
+fn parse_element(element: &Element, builder: &mut BookBuilder) -> Result<(), String>
+{
+    let style = element.attr("style").unwrap_or_default();
+    let base_style = style.trim_end_matches(|c: char| c.is_ascii_digit());
+
+    match element.name()
+    {
+        "book" => {
+            builder.code = element.attr("code").map(|c| c.to_ascii_uppercase());
+            return Ok(());
+        },
+        "para" if style == "h" => {
+            builder.name = Some(element.text().trim().to_owned());
+            return Ok(());
+        },
+        "para" if SKIPPED_PARA_STYLES.contains(&base_style) => return Ok(()),
+        "note" => return Ok(()),
+        "chapter" => {
+            if element.attr("eid").is_none()
+            {
+                builder.start_chapter(parse_number(element)?);
+            }
+            return Ok(());
+        },
+        "verse" => {
+            if element.attr("eid").is_some()
+            {
+                builder.finish_verse();
+            }
+            else
+            {
+                builder.start_verse(parse_number(element)?)?;
+            }
+            return Ok(());
+        },
+        "char" if style == "add" => {
+            builder.open_format(Format::Italic);
+            parse_children(element, builder)?;
+            builder.close_format(Format::Italic);
+            return Ok(());
+        },
+        "char" if style == "w" => {
+            parse_children(element, builder)?;
+            if let Some(numbers) = element.attr("strong")
+            {
+                builder.push_text(&usfm::get_strongs_tags(numbers));
+            }
+            return Ok(());
+        },
+        "char" if style == "wj" => {
+            builder.open_format(Format::Red);
+            parse_children(element, builder)?;
+            builder.close_format(Format::Red);
+            return Ok(());
+        },
+        "para" => {
+            // paragraphs separate the words on either side of them
+            builder.push_text(" ");
+            parse_children(element, builder)?;
+            builder.push_text(" ");
+            return Ok(());
+        },
+        _ => {}
+    }
+
+    parse_children(element, builder)
+}
+
+fn parse_children(element: &Element, builder: &mut BookBuilder) -> Result<(), String>
+{
+    for node in element.nodes()
+    {
+        match node
+        {
+            Node::Element(child) => parse_element(child, builder)?,
+            Node::Text(text) => builder.push_text(text),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_number(element: &Element) -> Result<std::num::NonZeroU32, String>
+{
+    let number = element.attr("number").unwrap_or_default();
+
+    // verse ranges such as `3-4` only keep the first number
+    number.split(['-', ','])
+        .next()
+        .and_then(|n| n.trim().parse::<u32>().ok())
+        .and_then(std::num::NonZeroU32::new)
+        .ok_or(format!("Expected a chapter or verse number, found `{}`", number))
+}
@@ -2,19 +2,25 @@ use cloud_sync::GoogleUserInfo;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use std::{cell::RefCell, collections::HashMap, io::Read, ops::Deref, path::PathBuf, sync::{Arc, Mutex, MutexGuard, RwLock}, thread::spawn};
+use std::{cell::RefCell, collections::HashMap, ops::Deref, path::{Path, PathBuf}, sync::{Arc, Mutex, MutexGuard, RwLock}, thread::spawn};
 use tauri::{
     path::{BaseDirectory, PathResolver}, AppHandle, Emitter, Runtime
 };
 
 use crate::{
-    audio::{reader_behavior::ReaderBehavior, TtsSettings}, bible::*, bible_parsing, cloud_sync::{sync_state::CloudSyncState, CloudEvent, RemoteSave}, debug_release_val, migration::{SaveVersion, CURRENT_SAVE_VERSION}, notes::{action::{Action, ActionHistory, ActionType, NotebookActionHandler}, *}, save_data::{AppSave, LocalDeviceSave, LocalDeviceSaveVersion, NotebookRecordSave, NotebookRecordSaveVersion}, settings::Settings
+    audio::{reader_behavior::ReaderBehavior, TtsSettings}, bible::*, bible_parsing::{self, cache, LazyBible}, cloud_sync::{sync_state::CloudSyncState, CloudEvent, RemoteSave}, debug_release_val, migration::{self, SaveVersion, CURRENT_SAVE_VERSION}, notes::{action::{Action, ActionHistory, ActionType, NotebookActionHandler}, *}, prompt, save_data::{AppSave, LocalDeviceSave, LocalDeviceSaveVersion, NotebookRecordSave, NotebookRecordSaveVersion}, searching::{history::SearchHistory, SearchIndex}, settings::Settings
 };
 
 pub const SAVE_NAME: &str = "save.json";
 
 pub const DEFAULT_BIBLE: &str = "KJV";
 
+/// Any OSIS files in this folder are loaded along side the `BIBLE_PATHS`
+pub const BIBLES_DIR: &str = "resources/bibles";
+
+/// Bibles imported by the user are copied into this folder, in the app data directory
+pub const USER_BIBLES_DIR: &str = "bibles";
+
 pub const BIBLE_PATHS: &[&str] = &[
     debug_release_val! { 
         debug: "resources/bibles/small_kjv.txt",
@@ -105,7 +111,10 @@ impl AppState
 }
 
 pub struct AppData {
-    bibles: HashMap<String, Arc<Bible>>,
+    /// Only the current version is kept loaded, the rest are loaded when needed
+    bibles: RwLock<HashMap<String, LazyBible>>,
+    bible_cache_dir: Option<PathBuf>,
+    app_handle: AppHandle,
     current_bible_version: Mutex<RefCell<String>>,
     
     pub save_version: SaveVersion,
@@ -125,6 +134,7 @@ pub struct AppData {
     reader_behavior: Mutex<RefCell<ReaderBehavior>>,
 
     recent_highlights: Mutex<RefCell<Vec<Uuid>>>,
+    search_history: Mutex<RefCell<SearchHistory>>,
 
     sync_state: RwLock<CloudSyncState>,
 }
@@ -139,21 +149,32 @@ impl AppData {
             .ok();
 
         let bible_paths = Self::get_bible_paths(resolver);
+        let bible_cache_dir = resolver.resolve(cache::BIBLE_CACHE_DIR, BaseDirectory::AppCache).ok();
 
-        Box::new(|| Self::new(save_path, bible_paths, app_handle))
+        Box::new(|| Self::new(save_path, bible_paths, bible_cache_dir, app_handle))
     }
 
-    fn new(save_path: Option<PathBuf>, bible_paths: Vec<PathBuf>, app_handle: AppHandle) -> Self
+    fn new(save_path: Option<PathBuf>, bible_paths: Vec<PathBuf>, bible_cache_dir: Option<PathBuf>, app_handle: AppHandle) -> Self
     {
         let file = save_path
             .and_then(|path| std::fs::read(path).ok())
             .and_then(|data| String::from_utf8(data).ok());
 
-        let bibles = Self::load_bibles(&bible_paths);
+        let bibles = Self::load_bibles(&bible_paths, bible_cache_dir.as_deref(), &app_handle);
 
         let (mut save, was_migrated, no_save) = match file {
             Some(file) => {
-                let (save, migrated) = AppSave::load(&file, &bibles);
+                // only saves from before notes were kept as actions need the Bibles to be migrated
+                let migration_bibles: HashMap<String, Arc<Bible>> = if migration::needs_bibles(&file)
+                {
+                    bibles.iter().filter_map(|(name, bible)| Some((name.clone(), bible.read().ok()?))).collect()
+                }
+                else
+                {
+                    HashMap::new()
+                };
+
+                let (save, migrated) = AppSave::load(&file, &migration_bibles);
                 app_handle.emit("loaded-tts-save", save.local_device_save.tts_settings).unwrap();
                 (save, migrated, false)
             },
@@ -175,38 +196,15 @@ impl AppData {
             DEFAULT_BIBLE.to_owned()
         };
 
-        if let ViewState::Chapter {
-            chapter,
-            scroll: _,
-            verse_range,
-        } = &mut save.local_device_save.view_states[save.local_device_save.view_state_index]
-        {
-            let bible = &bibles.get(&save.local_device_save.current_bible_version).map_or(bibles.get(DEFAULT_BIBLE).unwrap(), |b| b);
-
-            if chapter.book >= bible.books.len() as u32
-                || chapter.number >= bible.books[chapter.book as usize].chapters.len() as u32
-                || verse_range.map_or(false, |r| {
-                    r.end
-                        >= bible.books[chapter.book as usize].chapters[chapter.number as usize]
-                            .verses
-                            .len() as u32
-                })
-            {
-                chapter.book = 0;
-                chapter.number = 0;
-            }
-        }
-
-        let handlers = save.note_record_saves.into_iter().map(|notebook| {
-            let action_handler = NotebookActionHandler::new(notebook.history, &bibles);
-            (notebook.owner_id, action_handler)
-        }).collect::<HashMap<_, _>>();
+        let note_record_saves = std::mem::take(&mut save.note_record_saves);
 
-        Self {
-            bibles,
+        let data = Self {
+            bibles: RwLock::new(bibles),
+            bible_cache_dir,
+            app_handle,
             current_bible_version: Mutex::new(RefCell::new(save.local_device_save.current_bible_version)),
             save_version: CURRENT_SAVE_VERSION,
-            notebook_handlers: RwLock::new(handlers),
+            notebook_handlers: RwLock::new(HashMap::new()),
             view_state_index: Mutex::new(RefCell::new(save.local_device_save.view_state_index)),
             view_states: Mutex::new(RefCell::new(save.local_device_save.view_states)),
             editing_note: Mutex::new(RefCell::new(save.local_device_save.editing_note)),
@@ -216,8 +214,40 @@ impl AppData {
             selected_reading: Mutex::new(RefCell::new(save.local_device_save.selected_reading)),
             reader_behavior: Mutex::new(RefCell::new(save.local_device_save.reader_behavior)),
             recent_highlights: Mutex::new(RefCell::new(save.local_device_save.recent_highlights)),
+            search_history: Mutex::new(RefCell::new(save.local_device_save.search_history)),
             sync_state: RwLock::new(CloudSyncState::from_save(save.local_device_save.cloud_sync_save)), 
-        }
+        };
+
+        // loading the current version here means that if it can no longer be loaded, the default version is used from the start
+        let bible = data.get_current_bible();
+        let view_state_index = data.get_view_state_index();
+        data.read_view_states(|view_states| {
+            if let ViewState::Chapter { chapter, verse_range, .. } | ViewState::Compare { chapter, verse_range, .. } = &mut view_states[view_state_index]
+            {
+                if chapter.book >= bible.books.len() as u32
+                    || chapter.number >= bible.books[chapter.book as usize].chapters.len() as u32
+                    || verse_range.map_or(false, |r| {
+                        r.end
+                            >= bible.books[chapter.book as usize].chapters[chapter.number as usize]
+                                .verses
+                                .len() as u32
+                    })
+                {
+                    chapter.book = 0;
+                    chapter.number = 0;
+                }
+            }
+        });
+
+        let handlers = note_record_saves.into_iter().map(|notebook| {
+            (notebook.owner_id, data.create_handler(notebook.history))
+        }).collect();
+
+        *data.notebook_handlers.try_write().unwrap() = handlers;
+
+        // versions without a cache are parsed (and so loaded) when opened, and the migration may have loaded others
+        data.unload_unused_bibles();
+        data
     }
 
     pub fn save<R>(&self, resolver: &PathResolver<R>, tts_settings: TtsSettings)
@@ -234,6 +264,7 @@ impl AppData {
         let selected_reading = self.selected_reading.lock().unwrap().borrow().clone();
         let reader_behavior = self.reader_behavior.lock().unwrap().borrow().clone();
         let recent_highlights = self.recent_highlights.lock().unwrap().borrow().clone();
+        let search_history = self.search_history.lock().unwrap().borrow().clone();
         let cloud_sync_save = self.sync_state.try_read().unwrap().get_save();
 
         let note_record_saves = handlers.iter_mut().map(|(owner, handler)| {
@@ -257,6 +288,7 @@ impl AppData {
             reader_behavior,
             recent_highlights,
             cloud_sync_save,
+            search_history,
         };
 
         let save = AppSave {
@@ -300,25 +332,111 @@ impl AppData {
         current_bible.clone()
     }
 
-    pub fn get_current_bible(&self) -> &Bible
+    pub fn get_current_bible(&self) -> Arc<Bible>
     {
         let bible_version = self.get_current_bible_version();
-        self.bibles.get(&bible_version).map_or(self.bibles.get(DEFAULT_BIBLE).unwrap(), |b| b)
+        self.load_bible(&bible_version).unwrap_or_else(|| self.get_default_bible())
+    }
+
+    /// Gets the search index of the current version, which is built the first time the version is searched
+    pub fn get_current_search_index(&self) -> Arc<SearchIndex>
+    {
+        let bible_version = self.get_current_bible_version();
+        self.get_search_index(&bible_version)
+            .or_else(|| self.get_search_index(&DEFAULT_BIBLE.to_owned()))
+            .expect("The default Bible is always installed")
+    }
+
+    /// Gets the search index of a version. Like `get_bible`, only the index of the current version is kept
+    pub fn get_search_index(&self, name: &String) -> Option<Arc<SearchIndex>>
+    {
+        let is_current = *name == self.get_current_bible_version();
+        let index = self.bibles.read().unwrap().get(name).map(|bible| if is_current { bible.get_search_index() } else { bible.read_search_index() })?;
+
+        index.map_err(|err| self.remove_broken_bible(name, err)).ok()
     }
 
     pub fn get_default_bible(&self) -> Arc<Bible>
     {
-        self.bibles.get(DEFAULT_BIBLE).unwrap().clone()
+        self.load_bible(DEFAULT_BIBLE).expect("The default Bible is always installed")
     }
 
+    /// Gets a version, or None if it is not installed. Only the current version is kept in memory, any other version is only
+    /// loaded for as long as it is used
     pub fn get_bible(&self, name: &String) -> Option<Arc<Bible>>
     {
-        self.bibles.get(name).map(|b| b.clone())
+        self.load_bible(name)
+    }
+
+    fn load_bible(&self, name: &str) -> Option<Arc<Bible>>
+    {
+        let is_current = name == self.get_current_bible_version();
+        let bible = self.bibles.read().unwrap().get(name).map(|bible| if is_current { bible.get() } else { bible.read() })?;
+
+        bible.map_err(|err| self.remove_broken_bible(name, err)).ok()
+    }
+
+    /// A version that can no longer be loaded, such as an imported file that has since been deleted, is removed until the next
+    /// launch, and the user is told why. If it was the current version, the default version is used instead
+    fn remove_broken_bible(&self, name: &str, err: String)
+    {
+        if name == DEFAULT_BIBLE
+        {
+            panic!("Failed to load the default Bible `{}`: {}", DEFAULT_BIBLE, err);
+        }
+
+        self.bibles.write().unwrap().remove(name);
+
+        let mut message = format!("Could not load `{}`: {}", name, err);
+        if self.get_current_bible_version() == name
+        {
+            *self.current_bible_version.lock().unwrap().borrow_mut() = DEFAULT_BIBLE.to_owned();
+            message += &format!("\nThe {} version is being used instead.", DEFAULT_BIBLE);
+        }
+
+        prompt::notify_user(self.app_handle.clone(), "Failed to Load Bible".into(), message);
+    }
+
+    /// Loads the versions that the actions of a history were made in, to build its notebooks. Versions that were not already
+    /// loaded are only kept until the returned map is dropped
+    fn read_history_bibles(&self, history: &ActionHistory) -> HashMap<String, Arc<Bible>>
+    {
+        history.get_bible_names().into_iter().filter_map(|name| {
+            let bible = self.bibles.read().unwrap().get(&name)?.read();
+            let bible = bible.map_err(|err| self.remove_broken_bible(&name, err)).ok()?;
+            Some((name, bible))
+        }).collect()
+    }
+
+    /// Creates a handler with the notebook of the current version, which has the notes and highlights made in every version
+    fn create_handler(&self, history: ActionHistory) -> NotebookActionHandler
+    {
+        // the version is got after the Bible, in case it could not be loaded and the default version is used instead
+        let bible = self.get_current_bible();
+        let bible_version = self.get_current_bible_version();
+
+        let mut bibles = self.read_history_bibles(&history);
+        bibles.insert(bible_version.clone(), bible);
+        NotebookActionHandler::for_version(history, &bible_version, &bibles)
+    }
+
+    /// Gets the handler of the current owner, rebuilding its notebook if the current version has changed since it was built
+    fn get_current_handler<'a>(&self, handlers: &'a mut HashMap<Option<String>, NotebookActionHandler>) -> &'a mut NotebookActionHandler
+    {
+        let owner_id = self.sync_state.try_read().unwrap().get_owner_id();
+        let handler = handlers.entry(owner_id).or_insert_with(|| self.create_handler(ActionHistory::new()));
+
+        if !handler.get_notebooks().contains_key(&self.get_current_bible_version())
+        {
+            *handler = self.create_handler(handler.get_history().clone());
+        }
+
+        handler
     }
     
     pub fn set_current_bible_version(&self, version: String)
     {
-        if self.get_bibles().find(|v| **v == version).is_some()
+        if self.bibles.read().unwrap().contains_key(&version)
         {
             let mut version_binding = self.current_bible_version.lock().unwrap();
 
@@ -330,19 +448,78 @@ impl AppData {
             let mut note_binding = self.editing_note.lock().unwrap();
             *note_binding.get_mut() = None; 
         }
+
+        self.unload_unused_bibles();
+    }
+
+    pub fn get_bibles(&self) -> Vec<String>
+    {
+        self.bibles.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Validates and copies a Bible file into the `USER_BIBLES_DIR`, so that it is loaded on every launch, and makes it available 
+    /// immediately. Returns the name of the imported version
+    pub fn import_bible<R>(&self, resolver: &PathResolver<R>, path: &Path) -> Result<String, String>
+        where R : Runtime
+    {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read `{}`: {}", path.display(), e))?;
+
+        let bible = bible_parsing::parse_bible_file(path, &text)
+            .map_err(|e| format!("`{}` is not a valid Bible: {}", path.display(), e))?;
+
+        if bible.books.is_empty()
+        {
+            return Err(format!("`{}` does not contain any books", path.display()));
+        }
+
+        // held until the version is added, so that two imports of the same name can not both pass the check
+        let mut bibles = self.bibles.write().unwrap();
+        if bibles.contains_key(&bible.name)
+        {
+            return Err(format!("A Bible version named `{}` is already installed", bible.name));
+        }
+
+        let Some(file_name) = path.file_name() else {
+            return Err(format!("`{}` is not a file", path.display()));
+        };
+
+        let dir = resolver.resolve(USER_BIBLES_DIR, BaseDirectory::AppData)
+            .map_err(|e| format!("Could not find the app data directory: {}", e))?;
+
+        let destination = dir.join(file_name);
+        if destination.exists()
+        {
+            return Err(format!("A Bible file named `{}` has already been imported", file_name.to_string_lossy()));
+        }
+
+        std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::copy(path, &destination))
+            .map_err(|e| format!("Could not copy `{}` into `{}`: {}", path.display(), dir.display(), e))?;
+
+        let name = bible.name.clone();
+        let bible = LazyBible::from_parsed(bible, &destination, text.as_bytes(), self.bible_cache_dir.as_deref());
+        bibles.insert(name.clone(), bible);
+        drop(bibles);
+
+        self.unload_unused_bibles();
+        Ok(name)
     }
 
-    pub fn get_bibles(&self) -> impl Iterator<Item = &String>
+    /// Frees every loaded version except for the current one
+    fn unload_unused_bibles(&self)
     {
-        self.bibles.keys().into_iter()
+        let current = self.get_current_bible_version();
+        self.bibles.write().unwrap().iter_mut()
+            .filter(|(name, bible)| **name != current && bible.is_loaded())
+            .for_each(|(_, bible)| bible.unload());
     }
 
     pub fn read_current_notebook<F, R>(&self, mut f: F) -> R
         where F : FnMut(&Notebook) -> R
     {
         let mut handlers = self.notebook_handlers.try_write().unwrap();
-        let owner_id = self.sync_state.try_read().unwrap().get_owner_id();
-        let handler = handlers.entry(owner_id).or_insert(NotebookActionHandler::new(ActionHistory::new(), &self.bibles));
+        let handler = self.get_current_handler(&mut handlers);
 
         let current_bible_version = self.get_current_bible_version();
 
@@ -353,16 +530,19 @@ impl AppData {
     pub fn run_action_on_current_notebook(&self, action_type: ActionType)
     {
         let mut handlers = self.notebook_handlers.try_write().unwrap();
-        let owner_id = self.sync_state.try_read().unwrap().get_owner_id();
-        let handler = handlers.entry(owner_id).or_insert(NotebookActionHandler::new(ActionHistory::new(), &self.bibles));
+        let handler = self.get_current_handler(&mut handlers);
+
+        // the version is got after the Bible, in case it could not be loaded and the default version is used instead
+        let bible = self.get_current_bible();
+        let bible_version = self.get_current_bible_version();
 
         let action = Action {
-            notebook: self.get_current_bible_version(),
-            bible_name: self.get_current_bible_version(),
+            notebook: bible_version.clone(),
+            bible_name: bible_version.clone(),
             action: action_type,
         };
 
-        handler.push_action(action, &self.bibles);
+        handler.push_action(action, &HashMap::from([(bible_version, bible)]));
     }
 
     pub fn read_editing_note<F, R>(&self, mut f: F) -> R
@@ -436,11 +616,19 @@ impl AppData {
         f(&mut *recent_highlights)
     }
 
+    pub fn read_search_history<F, R>(&self, mut f: F) -> R 
+        where F : FnMut(&mut SearchHistory) -> R
+    {
+        let binding = self.search_history.lock().unwrap();
+        let mut search_history = binding.borrow_mut();
+        f(&mut search_history)
+    }
+
     pub fn get_remote_save(&self) -> RemoteSave
     {
         let mut handlers = self.notebook_handlers.try_write().unwrap();
         let owner_id = self.sync_state.try_read().unwrap().get_owner_id();
-        let handler = handlers.entry(owner_id).or_insert(NotebookActionHandler::new(ActionHistory::new(), &self.bibles));
+        let handler = handlers.entry(owner_id).or_insert_with(|| self.create_handler(ActionHistory::new()));
 
         let note_record_save = NotebookRecordSave {
             history: handler.get_history().clone(),
@@ -492,7 +680,7 @@ impl AppData {
         let remote = self.sync_state.try_read().unwrap().read_remote_save()?;
 
         let mut handlers = self.notebook_handlers.try_write().unwrap();
-        let handler = handlers.entry(user_id.clone()).or_insert(NotebookActionHandler::new(ActionHistory::new(), &self.bibles));
+        let handler = handlers.entry(user_id.clone()).or_insert_with(|| self.create_handler(ActionHistory::new()));
         let local = handler.get_history().clone();
 
         let merged = match remote
@@ -510,7 +698,7 @@ impl AppData {
         
         sync_state.write_remote_save(&remote_save)?;
 
-        *handler = NotebookActionHandler::new(merged, &self.bibles);
+        *handler = self.create_handler(merged);
 
         Ok(())
     }
@@ -537,31 +725,69 @@ impl AppData {
         };
 
         let user_handler = handlers.entry(Some(user_id))
-            .or_insert(NotebookActionHandler::new(ActionHistory::new(), &self.bibles));
+            .or_insert_with(|| self.create_handler(ActionHistory::new()));
 
         let merged = ActionHistory::merge(user_handler.get_history().clone(), unowned_handler.get_history().clone());
-        *user_handler = NotebookActionHandler::new(merged, &self.bibles);
+        *user_handler = self.create_handler(merged);
     }
 
     fn get_bible_paths<R>(path_resolver: &PathResolver<R>) -> Vec<PathBuf>
         where R : Runtime
     {
-        BIBLE_PATHS.iter().map(|relative_path| {
+        let mut paths = BIBLE_PATHS.iter().map(|relative_path| {
             path_resolver.resolve(relative_path, BaseDirectory::Resource)
                 .expect(&format!("Failed to resolve path `{}`", relative_path))
-        }).collect()
+        }).collect_vec();
+
+        let osis_paths = Self::get_dir_files(path_resolver.resolve(BIBLES_DIR, BaseDirectory::Resource).ok())
+            .filter(|path| bible_parsing::is_osis_file(path));
+
+        let user_paths = Self::get_dir_files(path_resolver.resolve(USER_BIBLES_DIR, BaseDirectory::AppData).ok())
+            .filter(|path| bible_parsing::is_bible_file(path));
+
+        paths.extend(osis_paths);
+        paths.extend(user_paths);
+        paths
+    }
+
+    fn get_dir_files(dir: Option<PathBuf>) -> impl Iterator<Item = PathBuf>
+    {
+        dir.and_then(|dir| std::fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .sorted()
     }
 
-    fn load_bibles(paths: &Vec<PathBuf>) -> HashMap<String, Arc<Bible>>
+    /// Any Bibles that fail to load are skipped, and the user is notified. Only Bibles without a cache are parsed
+    fn load_bibles(paths: &Vec<PathBuf>, cache_dir: Option<&Path>, app_handle: &AppHandle) -> HashMap<String, LazyBible>
     {
-        paths.iter().map(|path| {
-            let mut file = std::fs::File::open(&path).unwrap();
+        let mut bibles: HashMap<String, LazyBible> = HashMap::new();
+        for path in paths
+        {
+            match LazyBible::open(path, cache_dir)
+            {
+                Ok(bible) if bibles.contains_key(bible.name()) => {
+                    let message = format!("`{}` was not loaded, as a Bible version named `{}` is already loaded", path.display(), bible.name());
+                    prompt::notify_user(app_handle.clone(), "Duplicate Bible Version".into(), message);
+                },
+                Ok(bible) => {
+                    bibles.insert(bible.name().to_owned(), bible);
+                },
+                Err(err) => {
+                    let message = format!("Could not load `{}`: {}", path.display(), err);
+                    prompt::notify_user(app_handle.clone(), "Failed to Load Bible".into(), message);
+                },
+            }
+        }
 
-            let mut text = String::new();
-            file.read_to_string(&mut text).unwrap();
-            let bible = bible_parsing::parse_bible(&text).unwrap();
-            (bible.name.clone(), Arc::new(bible))
-        }).collect::<HashMap<_, _>>()
+        if let Some(cache_dir) = cache_dir
+        {
+            cache::remove_unused_caches(cache_dir, bibles.values().filter_map(|b| b.cache_path()));
+        }
+
+        bibles
     }
 }
 
@@ -579,4 +805,11 @@ pub enum ViewState {
         scroll: f32,
         note_editing_location: Option<ReferenceLocation>,
     },
+    /// The same chapter in several versions, side by side. The chapter is in the numbering of the current version
+    Compare {
+        chapter: ChapterIndex,
+        verse_range: Option<VerseRange>,
+        versions: Vec<String>,
+        scroll: f32,
+    },
 }
@@ -21,7 +21,7 @@ pub fn run_tts_command(state: State<'_, Mutex<TtsPlayer>>, app_state: State<'_,
 
             if let Some(Ok(key)) = args.map(|a| serde_json::from_value::<PassageAudioKey>(a))
             {
-                let bible = app_state.get_bible(&key.bible_name).unwrap_or(app_state.get_default_bible());
+                let bible = app_state.get_bible(&key.bible_name).unwrap_or_else(|| app_state.get_default_bible());
 
                 let request = state.lock().unwrap().request_tts(bible, key.chapter, key.verse_range);
                 let request_str = serde_json::to_string(&request).unwrap();
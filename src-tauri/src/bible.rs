@@ -1,11 +1,43 @@
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use crate::versification::Versification;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Word {
     pub text: String,
     pub italicized: bool,
     pub red: bool,
+    /// The Strong's numbers of the original words that this word translates, such as `H7225` or `G26`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strongs: Vec<String>,
+    /// The morphology (part of speech, tense, etc) of the original word, in the code system of the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub morph: Option<String>,
+}
+
+/// All the Strong's information of a single word, see `Word`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WordStrongs {
+    pub text: String,
+    pub strongs: Vec<String>,
+    pub morph: Option<String>,
+}
+
+/// Normalizes a Strong's number, so that `H07225`, `h7225` and `strong:H7225` are all `H7225`.
+/// Returns `None` if it is not a Strong's number
+pub fn normalize_strongs(number: &str) -> Option<String> {
+    let number = number.trim();
+    let number = number.strip_prefix("strong:").unwrap_or(number);
+
+    let mut chars = number.chars();
+    let testament = chars.next()?.to_ascii_uppercase();
+    let digits = chars.as_str().trim_start_matches('0');
+    if !matches!(testament, 'H' | 'G') || !digits.starts_with(|c: char| c.is_ascii_digit() && c != '0') {
+        return None;
+    }
+
+    Some(format!("{}{}", testament, digits))
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,9 +57,22 @@ impl Chapter {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Testament {
+    #[default]
+    Old,
+    New,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Book {
     pub name: String,
+    /// The canonical id of the book (`GEN`, `EXO`, `1SA`...), which is the same across all versions. See `canon`
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub testament: Testament,
     pub chapters: Vec<Chapter>,
 }
 
@@ -36,6 +81,8 @@ pub struct Bible {
     pub name: String,
     pub desc: String,
     pub books: Vec<Book>,
+    #[serde(default)]
+    pub versification: Versification,
 }
 
 impl Bible {
@@ -44,6 +91,8 @@ impl Bible {
             .iter()
             .map(|b| BookView {
                 name: b.name.clone(),
+                id: b.id.clone(),
+                testament: b.testament,
                 chapter_count: b.chapters.len() as u32,
             })
             .collect_vec();
@@ -57,6 +106,11 @@ impl Bible {
     pub fn get_chapter(&self, index: ChapterIndex) -> &Chapter {
         &self.books[index.book as usize].chapters[index.number as usize]
     }
+
+    /// Gets the index of a book from its canonical id, as books can be missing or in a different order between versions
+    pub fn get_book_index(&self, id: &str) -> Option<u32> {
+        self.books.iter().position(|b| b.id.eq_ignore_ascii_case(id)).map(|i| i as u32)
+    }
 }
 
 pub struct BibleView
@@ -106,6 +160,8 @@ pub struct ChapterIndex {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookView {
     pub name: String,
+    pub id: String,
+    pub testament: Testament,
     pub chapter_count: u32,
 }
 
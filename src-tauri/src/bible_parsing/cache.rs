@@ -0,0 +1,414 @@
+use std::{collections::HashSet, fs::File, io::{BufReader, BufWriter, Read, Write}, path::{Path, PathBuf}, sync::{Arc, OnceLock}};
+
+use crate::{bible::*, searching::SearchIndex, utils, versification::Versification};
+
+// Parsing the text and OSIS formats is slow for full Bibles, so each parsed Bible is written to a compact binary cache,
+// named by the hash of the source file. The cache starts with a small header (the name and description), so the
+// available versions can be listed without reading any of the text.
+//
+// format (all integers are little endian u32, strings are a length followed by utf-8 bytes):
+//   magic, version, name, description, versification, book count,
+//   books: name, id, testament, chapter count, chapters: verse count, verses: word count,
+//   words: flags, text, Strong's numbers (a list, only if flagged), morphology (only if flagged)
+
+/// The folder, in the app cache directory, that the cached Bibles are stored in
+pub const BIBLE_CACHE_DIR: &str = "bibles";
+
+const CACHE_EXTENSION: &str = "bin";
+const CACHE_MAGIC: &[u8; 4] = b"ABBC";
+
+/// Must be incremented whenever the format changes, so that old caches are regenerated
+const CACHE_VERSION: u32 = 4;
+
+const ITALICIZED_FLAG: u8 = 1 << 0;
+const RED_FLAG: u8 = 1 << 1;
+const STRONGS_FLAG: u8 = 1 << 2;
+const MORPH_FLAG: u8 = 1 << 3;
+
+/// A Bible that is only loaded into memory when it is used, and can be unloaded again when it is not needed
+#[derive(Debug)]
+pub struct LazyBible
+{
+    name: String,
+    source: PathBuf,
+    cache: Option<PathBuf>,
+    bible: OnceLock<Arc<Bible>>,
+    search_index: OnceLock<Arc<SearchIndex>>,
+}
+
+impl LazyBible
+{
+    /// Opens the Bible at `path`. If it has a cache, only the header is read, otherwise it is parsed and a cache is written
+    pub fn open(path: &Path, cache_dir: Option<&Path>) -> Result<Self, String>
+    {
+        let data = std::fs::read(path).map_err(|e| e.to_string())?;
+        let cache = cache_dir.map(|dir| get_cache_path(dir, &data));
+
+        if let Some(name) = cache.as_deref().and_then(|c| read_cache_header(c).ok())
+        {
+            return Ok(Self {
+                name,
+                source: path.to_owned(),
+                cache,
+                bible: OnceLock::new(),
+                search_index: OnceLock::new(),
+            });
+        }
+
+        let text = String::from_utf8(data).map_err(|_| "The file is not valid utf-8".to_owned())?;
+        let bible = super::parse_bible_file(path, &text)?;
+        Ok(Self::from_parsed(bible, path, text.as_bytes(), cache_dir))
+    }
+
+    /// Creates an already loaded Bible, writing a cache for it. `data` is the contents of the `source` file
+    pub fn from_parsed(bible: Bible, source: &Path, data: &[u8], cache_dir: Option<&Path>) -> Self
+    {
+        let cache = cache_dir.map(|dir| get_cache_path(dir, data));
+        if let Some(cache) = &cache
+        {
+            // a missing cache only means that the Bible is parsed again next time
+            if let Err(err) = write_cache(cache, &bible)
+            {
+                println!("ERROR: Failed to write Bible cache `{}`: {}", cache.display(), err);
+            }
+        }
+
+        Self {
+            name: bible.name.clone(),
+            source: source.to_owned(),
+            cache,
+            bible: OnceLock::from(Arc::new(bible)),
+            search_index: OnceLock::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    pub fn cache_path(&self) -> Option<&Path>
+    {
+        self.cache.as_deref()
+    }
+
+    pub fn is_loaded(&self) -> bool
+    {
+        self.bible.get().is_some()
+    }
+
+    /// Gets the Bible, loading it from the cache (or the source file, if the cache is missing) and keeping it in memory if it
+    /// is not already loaded. Gives an error if neither can be read
+    pub fn get(&self) -> Result<Arc<Bible>, String>
+    {
+        if let Some(bible) = self.bible.get()
+        {
+            return Ok(bible.clone());
+        }
+
+        let bible = self.load()?;
+        Ok(self.bible.get_or_init(|| Arc::new(bible)).clone())
+    }
+
+    /// Gets the Bible without keeping it in memory, for when it is only needed briefly. If it is already loaded, that copy is used
+    pub fn read(&self) -> Result<Arc<Bible>, String>
+    {
+        match self.bible.get()
+        {
+            Some(bible) => Ok(bible.clone()),
+            None => self.load().map(Arc::new),
+        }
+    }
+
+    /// Gets the search index of the Bible, loading the Bible and building the index the first time it is needed
+    pub fn get_search_index(&self) -> Result<Arc<SearchIndex>, String>
+    {
+        if let Some(index) = self.search_index.get()
+        {
+            return Ok(index.clone());
+        }
+
+        let bible = self.get()?;
+        let index = SearchIndex::new(&bible);
+        Ok(self.search_index.get_or_init(|| Arc::new(index)).clone())
+    }
+
+    /// Gets the search index without keeping it or the Bible in memory, like `read`. If the index is already built, it is used
+    pub fn read_search_index(&self) -> Result<Arc<SearchIndex>, String>
+    {
+        match self.search_index.get()
+        {
+            Some(index) => Ok(index.clone()),
+            None => self.read().map(|bible| Arc::new(SearchIndex::new(&bible))),
+        }
+    }
+
+    /// Frees the loaded Bible and its search index. Anything still holding onto them keeps them alive until they are done with them
+    pub fn unload(&mut self)
+    {
+        self.bible = OnceLock::new();
+        self.search_index = OnceLock::new();
+    }
+
+    fn load(&self) -> Result<Bible, String>
+    {
+        let cached = self.cache.as_deref().and_then(|cache| read_cache(cache).ok());
+        match cached
+        {
+            Some(bible) => Ok(bible),
+            None => {
+                let text = std::fs::read_to_string(&self.source).map_err(|e| e.to_string())?;
+                super::parse_bible_file(&self.source, &text)
+            },
+        }
+    }
+}
+
+pub fn get_cache_path(cache_dir: &Path, data: &[u8]) -> PathBuf
+{
+    let hash = utils::get_hash_code(&data);
+    cache_dir.join(format!("{:016x}.{}", hash, CACHE_EXTENSION))
+}
+
+/// Deletes any caches in `cache_dir` that are not in `used`, such as the caches of Bible files that have since changed
+pub fn remove_unused_caches<'a>(cache_dir: &Path, used: impl IntoIterator<Item = &'a Path>)
+{
+    let used = used.into_iter().collect::<HashSet<_>>();
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+
+    entries.filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == CACHE_EXTENSION) && !used.contains(p.as_path()))
+        .for_each(|p| {
+            let _ = std::fs::remove_file(p);
+        });
+}
+
+pub fn write_cache(path: &Path, bible: &Bible) -> Result<(), String>
+{
+    if let Some(dir) = path.parent()
+    {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    encode_bible(&mut writer, bible).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())
+}
+
+pub fn read_cache(path: &Path) -> Result<Bible, String>
+{
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    decode_bible(&mut BufReader::new(file))
+}
+
+/// Reads only the name of a cached Bible
+pub fn read_cache_header(path: &Path) -> Result<String, String>
+{
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    decode_header(&mut BufReader::new(file)).map(|(name, _)| name)
+}
+
+pub fn encode_bible(writer: &mut impl Write, bible: &Bible) -> std::io::Result<()>
+{
+    writer.write_all(CACHE_MAGIC)?;
+    write_u32(writer, CACHE_VERSION)?;
+    write_str(writer, &bible.name)?;
+    write_str(writer, &bible.desc)?;
+    writer.write_all(&[encode_versification(bible.versification)])?;
+
+    write_u32(writer, bible.books.len() as u32)?;
+    for book in &bible.books
+    {
+        write_str(writer, &book.name)?;
+        write_str(writer, &book.id)?;
+        writer.write_all(&[encode_testament(book.testament)])?;
+        write_u32(writer, book.chapters.len() as u32)?;
+        for chapter in &book.chapters
+        {
+            write_u32(writer, chapter.verses.len() as u32)?;
+            for verse in &chapter.verses
+            {
+                write_u32(writer, verse.words.len() as u32)?;
+                for word in &verse.words
+                {
+                    let mut flags = 0;
+                    if word.italicized { flags |= ITALICIZED_FLAG; }
+                    if word.red { flags |= RED_FLAG; }
+                    if !word.strongs.is_empty() { flags |= STRONGS_FLAG; }
+                    if word.morph.is_some() { flags |= MORPH_FLAG; }
+
+                    writer.write_all(&[flags])?;
+                    write_str(writer, &word.text)?;
+
+                    if !word.strongs.is_empty()
+                    {
+                        write_u32(writer, word.strongs.len() as u32)?;
+                        for number in &word.strongs
+                        {
+                            write_str(writer, number)?;
+                        }
+                    }
+
+                    if let Some(morph) = &word.morph
+                    {
+                        write_str(writer, morph)?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn decode_bible(reader: &mut impl Read) -> Result<Bible, String>
+{
+    let (name, desc) = decode_header(reader)?;
+
+    let mut versification = [0u8];
+    reader.read_exact(&mut versification).map_err(|e| e.to_string())?;
+    let versification = decode_versification(versification[0])?;
+
+    let books = read_list(reader, |reader| {
+        let name = read_str(reader)?;
+        let id = read_str(reader)?;
+
+        let mut testament = [0u8];
+        reader.read_exact(&mut testament)?;
+        let testament = decode_testament(testament[0])?;
+
+        let chapters = read_list(reader, |reader| {
+            let verses = read_list(reader, |reader| {
+                let words = read_list(reader, |reader| {
+                    let mut flags = [0u8];
+                    reader.read_exact(&mut flags)?;
+                    let flags = flags[0];
+
+                    let text = read_str(reader)?;
+                    let strongs = if flags & STRONGS_FLAG != 0 { read_list(reader, read_str)? } else { vec![] };
+                    let morph = if flags & MORPH_FLAG != 0 { Some(read_str(reader)?) } else { None };
+
+                    Ok(Word {
+                        text,
+                        italicized: flags & ITALICIZED_FLAG != 0,
+                        red: flags & RED_FLAG != 0,
+                        strongs,
+                        morph,
+                    })
+                })?;
+
+                Ok(Verse { words })
+            })?;
+
+            Ok(Chapter { verses })
+        })?;
+
+        Ok(Book { name, id, testament, chapters })
+    }).map_err(|e| format!("Invalid Bible cache: {}", e))?;
+
+    Ok(Bible { name, desc, books, versification })
+}
+
+fn decode_header(reader: &mut impl Read) -> Result<(String, String), String>
+{
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|e| e.to_string())?;
+    if &magic != CACHE_MAGIC
+    {
+        return Err("Not a Bible cache".into());
+    }
+
+    let version = read_u32(reader).map_err(|e| e.to_string())?;
+    if version != CACHE_VERSION
+    {
+        return Err(format!("Expected cache version {}, found {}", CACHE_VERSION, version));
+    }
+
+    let name = read_str(reader).map_err(|e| e.to_string())?;
+    let desc = read_str(reader).map_err(|e| e.to_string())?;
+    Ok((name, desc))
+}
+
+fn encode_versification(versification: Versification) -> u8
+{
+    match versification
+    {
+        Versification::Kjv => 0,
+        Versification::Vulgate => 1,
+        Versification::Lxx => 2,
+    }
+}
+
+fn decode_versification(value: u8) -> Result<Versification, String>
+{
+    match value
+    {
+        0 => Ok(Versification::Kjv),
+        1 => Ok(Versification::Vulgate),
+        2 => Ok(Versification::Lxx),
+        _ => Err(format!("Invalid Bible cache: unknown versification {}", value)),
+    }
+}
+
+fn encode_testament(testament: Testament) -> u8
+{
+    match testament
+    {
+        Testament::Old => 0,
+        Testament::New => 1,
+    }
+}
+
+fn decode_testament(value: u8) -> std::io::Result<Testament>
+{
+    match value
+    {
+        0 => Ok(Testament::Old),
+        1 => Ok(Testament::New),
+        _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unknown testament {}", value))),
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> std::io::Result<()>
+{
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_str(writer: &mut impl Write, value: &str) -> std::io::Result<()>
+{
+    write_u32(writer, value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32>
+{
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_str(reader: &mut impl Read) -> std::io::Result<String>
+{
+    let length = read_u32(reader)? as u64;
+
+    // read through `take`, so that a corrupt length can not allocate more than is left in the file
+    let mut bytes = vec![];
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length
+    {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "string is longer than the rest of the file"));
+    }
+
+    String::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+fn read_list<R, T, F>(reader: &mut R, mut f: F) -> std::io::Result<Vec<T>>
+    where R : Read,
+          F : FnMut(&mut R) -> std::io::Result<T>
+{
+    let count = read_u32(reader)?;
+    (0..count).map(|_| f(reader)).collect()
+}
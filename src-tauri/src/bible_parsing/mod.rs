@@ -0,0 +1,298 @@
+pub mod cache;
+pub mod osis;
+mod test;
+
+use std::path::Path;
+
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::{bible::*, canon, versification::Versification};
+
+pub use cache::LazyBible;
+pub use osis::parse_osis_bible;
+
+pub const OSIS_EXTENSIONS: &[&str] = &["xml", "osis"];
+pub const TEXT_EXTENSIONS: &[&str] = &["txt"];
+
+// format: Book Name 10:5 rest of the verse text
+// `[...]` marks italicized words, and `{...}` marks red letter words (the words of Christ).
+// `<H1234>` or `<G1234>` after a word gives its Strong's number (a word can have several), and `<(...)>` its morphology
+
+pub fn parse_verse(text: &str) -> Verse {
+    let mut italicized = false;
+    let mut red = false;
+
+    let mut word = WordBuilder::default();
+    let mut words = vec![];
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => italicized = true,
+            ']' => italicized = false,
+            '{' => red = true,
+            '}' => red = false,
+            '<' => {
+                let tag = chars.by_ref().take_while(|c| *c != '>').collect::<String>();
+
+                // tags belong to the word before them, even if separated by whitespace, which is then dropped so the
+                // verse is split into the same words as it would be without the tag
+                if word.format.is_none() {
+                    word.text.clear();
+                }
+
+                let last = words.iter_mut().rev().find(|w: &&mut Word| !w.text.trim().is_empty());
+                let (strongs, morph) = match last {
+                    Some(last) if word.format.is_none() => (&mut last.strongs, &mut last.morph),
+                    _ => (&mut word.strongs, &mut word.morph),
+                };
+
+                if let Some(number) = normalize_strongs(&tag) {
+                    strongs.push(number);
+                }
+                else if let Some(m) = tag.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+                    *morph = Some(m.to_owned());
+                }
+            },
+            c if c.is_whitespace() => {
+                word.push_to(&mut words);
+                word.text.push(c);
+            },
+            c => {
+                // the formatting of a word comes from its first non-whitespace character
+                if word.format.is_none() {
+                    word.format = Some((italicized, red));
+                }
+
+                word.text.push(c);
+            }
+        }
+    }
+
+    word.push_to(&mut words);
+    Verse { words }
+}
+
+#[derive(Debug, Default)]
+struct WordBuilder {
+    text: String,
+    format: Option<(bool, bool)>,
+    strongs: Vec<String>,
+    morph: Option<String>,
+}
+
+impl WordBuilder {
+    /// Whitespace only words have no formatting
+    fn push_to(&mut self, words: &mut Vec<Word>) {
+        if self.text.is_empty() { return; }
+
+        let word = std::mem::take(self);
+        let (italicized, red) = word.format.unwrap_or_default();
+        words.push(Word {
+            text: word.text,
+            italicized,
+            red,
+            strongs: word.strongs,
+            morph: word.morph,
+        });
+    }
+}
+
+pub fn parse_bible(text: &str) -> Result<Bible, String> {
+    let pattern = Regex::new(r"\s*(?<book>[1-3]?\s*\p{L}+(?:\s+\p{L}+)*)\s+(?<chapter>\d+):(?<verse>\d+)(?:(?<text>[^\n]*))").unwrap();
+
+    let mut lines = text.lines();
+    let Some(version) = lines.next() else {
+        return Err("Missing Bible version".into());
+    };
+
+    let Some(description) = lines.next() else {
+        return Err("Missing Bible description".into());
+    };
+
+    let rest = lines.join("\n");
+
+    let mut books: Vec<Book> = vec![];
+    let mut last_chapter_number = 0;
+    for cap in pattern.captures_iter(&rest)
+    {
+        let book_name = cap.name("book").unwrap().as_str();
+        let chapter_str = cap.name("chapter").unwrap().as_str();
+        let text = cap.name("text").map(|m| m.as_str()).unwrap_or("");
+        let chapter_number = chapter_str.parse().unwrap();
+
+        if books.is_empty() || books.last().unwrap().name != book_name {
+            books.push(Book {
+                name: book_name.into(),
+                id: String::new(),
+                testament: Testament::Old,
+                chapters: vec![],
+            });
+        }
+
+        let book = books.last_mut().unwrap();
+
+        if book.chapters.is_empty() || last_chapter_number != chapter_number {
+            book.chapters.push(Chapter { verses: vec![] });
+            last_chapter_number = chapter_number;
+        }
+
+        let chapter = book.chapters.last_mut().unwrap();
+
+        chapter.verses.push(parse_verse(text));
+    }
+
+    set_book_ids(&mut books);
+
+    Ok(Bible {
+        name: version.into(),
+        desc: description.into(),
+        books,
+        versification: Versification::Kjv,
+    })
+}
+
+/// Sets the canonical id and testament of each book from its name. If a name is not known, but the Bible has every
+/// book of the canon, the books are assumed to be in canonical order. Any other books keep their name as their id
+fn set_book_ids(books: &mut [Book])
+{
+    let is_complete = books.len() == canon::BOOKS.len();
+    let mut testament = Testament::Old;
+    for (i, book) in books.iter_mut().enumerate()
+    {
+        let canon_book = canon::find_book(&book.name).or(if is_complete { canon::BOOKS.get(i) } else { None });
+        match canon_book
+        {
+            Some(canon_book) => {
+                book.id = canon_book.id.into();
+                book.testament = canon_book.testament;
+            },
+            None => {
+                book.id = book.name.to_uppercase();
+                book.testament = testament;
+            }
+        }
+
+        testament = book.testament;
+    }
+}
+
+
+/// Parses a bible file, choosing the format based on the file extension
+pub fn parse_bible_file(path: &Path, text: &str) -> Result<Bible, String>
+{
+    if is_osis_file(path)
+    {
+        parse_osis_bible(text)
+    }
+    else 
+    {
+        parse_bible(text)
+    }
+}
+
+pub fn is_osis_file(path: &Path) -> bool
+{
+    has_extension(path, OSIS_EXTENSIONS)
+}
+
+/// If the file is in one of the formats supported by `parse_bible_file`
+pub fn is_bible_file(path: &Path) -> bool
+{
+    has_extension(path, TEXT_EXTENSIONS) || is_osis_file(path)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool
+{
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|o| e.eq_ignore_ascii_case(o)))
+}
+
+/// Builds up a verse from formatted text segments, using the same word format as `parse_verse`.
+/// Every word keeps a single leading space, and takes its formatting from its first character
+#[derive(Debug, Default)]
+pub struct VerseBuilder
+{
+    words: Vec<Word>,
+    word: String,
+    italicized: bool,
+    red: bool,
+    strongs: Vec<String>,
+    morph: Option<String>,
+}
+
+impl VerseBuilder
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn push_text(&mut self, text: &str, italicized: bool, red: bool)
+    {
+        for c in text.chars()
+        {
+            if c.is_whitespace()
+            {
+                self.push_word();
+                continue;
+            }
+
+            if self.word.is_empty()
+            {
+                self.word.push(' ');
+                self.italicized = italicized;
+                self.red = red;
+            }
+
+            self.word.push(c);
+        }
+    }
+
+    /// The number of finished words, for use with `tag_words`
+    pub fn word_count(&self) -> usize
+    {
+        self.words.len()
+    }
+
+    /// Adds Strong's numbers and morphology to every word since `start` (a `word_count`), including the unfinished word
+    pub fn tag_words(&mut self, start: usize, strongs: &[String], morph: Option<&str>)
+    {
+        let words = self.words.iter_mut().skip(start).map(|w| (&mut w.strongs, &mut w.morph));
+        let current = (!self.word.is_empty()).then_some((&mut self.strongs, &mut self.morph));
+
+        for (word_strongs, word_morph) in words.chain(current)
+        {
+            for number in strongs.iter().filter(|s| !word_strongs.contains(s)).collect::<Vec<_>>()
+            {
+                word_strongs.push(number.clone());
+            }
+
+            if morph.is_some()
+            {
+                *word_morph = morph.map(|m| m.to_owned());
+            }
+        }
+    }
+
+    pub fn build(mut self) -> Verse
+    {
+        self.push_word();
+        Verse { words: self.words }
+    }
+
+    fn push_word(&mut self)
+    {
+        if self.word.is_empty() { return; }
+
+        self.words.push(Word {
+            text: std::mem::take(&mut self.word),
+            italicized: self.italicized,
+            red: self.red,
+            strongs: std::mem::take(&mut self.strongs),
+            morph: self.morph.take(),
+        });
+    }
+}
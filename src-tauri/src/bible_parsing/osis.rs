@@ -0,0 +1,222 @@
+use minidom::{Element, NSChoice, Node};
+
+use crate::{bible::*, canon, versification::Versification};
+
+use super::VerseBuilder;
+
+// format: OSIS 2.1 xml, see https://crosswire.org/osis/
+// Both the container (`<verse osisID="..">text</verse>`) and milestone (`<verse sID=".."/>text<verse eID=".."/>`)
+// forms are supported, as most public domain OSIS files use one or the other
+
+pub fn parse_osis_bible(text: &str) -> Result<Bible, String>
+{
+    let root: Element = text.parse().map_err(|e| format!("Invalid OSIS xml: {}", e))?;
+
+    let Some(osis_text) = root.children().find(|c| c.is("osisText", NSChoice::Any)) else {
+        return Err("Missing `osisText` element".into());
+    };
+
+    let work = osis_text.children()
+        .find(|c| c.is("header", NSChoice::Any))
+        .and_then(|h| h.children().find(|c| c.is("work", NSChoice::Any)));
+
+    let Some(name) = osis_text.attr("osisIDWork").or(work.and_then(|w| w.attr("osisWork"))) else {
+        return Err("Missing Bible version, expected an `osisIDWork` attribute".into());
+    };
+
+    let desc = work
+        .and_then(|w| w.children().find(|c| c.is("title", NSChoice::Any)))
+        .map(|t| t.text().trim().to_owned())
+        .unwrap_or_default();
+
+    let versification = work
+        .and_then(|w| w.children().find(|c| c.is("refSystem", NSChoice::Any)))
+        .map_or(Versification::default(), |r| Versification::from_ref_system(&r.text()));
+
+    let mut parser = OsisParser {
+        books: vec![],
+        book_id: None,
+        chapter_number: 0,
+        verse: None,
+        italicized: 0,
+        red: 0,
+    };
+
+    parser.parse_element(osis_text)?;
+    parser.finish_verse();
+
+    Ok(Bible {
+        name: name.into(),
+        desc,
+        books: parser.books,
+        versification,
+    })
+}
+
+struct OsisParser
+{
+    books: Vec<Book>,
+    book_id: Option<String>,
+    chapter_number: u32,
+    verse: Option<VerseBuilder>,
+
+    // depths, as `<transChange>` and `<q>` elements can be nested
+    italicized: u32,
+    red: u32,
+}
+
+impl OsisParser
+{
+    fn parse_element(&mut self, element: &Element) -> Result<(), String>
+    {
+        match element.name()
+        {
+            "header" | "note" | "rdg" => return Ok(()), // not part of the verse text
+            "title" if element.attr("canonical") != Some("true") => return Ok(()),
+            "verse" => {
+                if element.attr("eID").is_some()
+                {
+                    self.finish_verse();
+                    return Ok(());
+                }
+
+                if let Some(id) = element.attr("sID").or(element.attr("osisID"))
+                {
+                    self.start_verse(id)?;
+                }
+
+                self.parse_children(element)?;
+
+                // container verses end with the element, milestones have an explicit `eID`
+                if element.attr("sID").is_none()
+                {
+                    self.finish_verse();
+                }
+
+                return Ok(());
+            },
+            "w" => {
+                // `<w lemma="strong:H07225" morph="strongMorph:TH8804">beginning</w>`
+                let start = self.verse.as_ref().map_or(0, |v| v.word_count());
+                self.parse_children(element)?;
+
+                let strongs = element.attr("lemma").unwrap_or_default()
+                    .split_whitespace()
+                    .filter_map(normalize_strongs)
+                    .collect::<Vec<_>>();
+
+                // the morphology system prefix (such as `robinson:`) is not kept
+                let morph = element.attr("morph").map(|m| {
+                    m.split_whitespace().map(|p| p.split_once(':').map_or(p, |(_, m)| m)).collect::<Vec<_>>().join(" ")
+                });
+
+                if let Some(verse) = &mut self.verse
+                {
+                    verse.tag_words(start, &strongs, morph.as_deref());
+                }
+
+                return Ok(());
+            },
+            "transChange" => {
+                self.italicized += 1;
+                self.parse_children(element)?;
+                self.italicized -= 1;
+                return Ok(());
+            },
+            "q" if element.attr("who") == Some("Jesus") => {
+                if element.attr("sID").is_some()
+                {
+                    self.red += 1;
+                }
+                else if element.attr("eID").is_some()
+                {
+                    self.red = self.red.saturating_sub(1);
+                }
+                else
+                {
+                    self.red += 1;
+                    self.parse_children(element)?;
+                    self.red -= 1;
+                }
+
+                return Ok(());
+            },
+            _ => {}
+        }
+
+        self.parse_children(element)
+    }
+
+    fn parse_children(&mut self, element: &Element) -> Result<(), String>
+    {
+        for node in element.nodes()
+        {
+            match node
+            {
+                Node::Element(child) => self.parse_element(child)?,
+                Node::Text(text) => {
+                    if let Some(verse) = &mut self.verse
+                    {
+                        verse.push_text(text, self.italicized > 0, self.red > 0);
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start_verse(&mut self, osis_id: &str) -> Result<(), String>
+    {
+        self.finish_verse();
+
+        // combined verses are written as `Gen.1.1 Gen.1.2`, so only the first is used
+        let osis_id = osis_id.split_whitespace().next().unwrap_or(osis_id);
+        let mut parts = osis_id.split('.');
+
+        let (Some(book_id), Some(chapter), Some(_)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("Invalid verse osisID `{}`", osis_id));
+        };
+
+        let Ok(chapter_number) = chapter.parse::<u32>() else {
+            return Err(format!("Invalid chapter number in osisID `{}`", osis_id));
+        };
+
+        if self.book_id.as_deref() != Some(book_id)
+        {
+            // books outside of the canon (such as the apocrypha) keep their OSIS id
+            let canon_book = canon::get_osis_book(book_id);
+            self.books.push(Book {
+                name: canon_book.map_or(book_id, |b| b.name).into(),
+                id: canon_book.map_or(book_id.to_uppercase(), |b| b.id.into()),
+                testament: canon_book.map_or(Testament::Old, |b| b.testament),
+                chapters: vec![],
+            });
+
+            self.book_id = Some(book_id.into());
+            self.chapter_number = 0;
+        }
+
+        let book = self.books.last_mut().unwrap();
+        if book.chapters.is_empty() || self.chapter_number != chapter_number
+        {
+            book.chapters.push(Chapter { verses: vec![] });
+            self.chapter_number = chapter_number;
+        }
+
+        self.verse = Some(VerseBuilder::new());
+        Ok(())
+    }
+
+    fn finish_verse(&mut self)
+    {
+        let Some(verse) = self.verse.take() else {
+            return;
+        };
+
+        if let Some(chapter) = self.books.last_mut().and_then(|b| b.chapters.last_mut())
+        {
+            chapter.verses.push(verse.build());
+        }
+    }
+}
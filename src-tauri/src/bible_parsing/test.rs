@@ -0,0 +1,309 @@
+#[cfg(test)]
+mod tests {
+    use crate::bible::{Bible, Testament, Verse};
+    use crate::canon;
+    use crate::bible_parsing::{cache, parse_bible, parse_osis_bible, parse_verse, LazyBible};
+
+    const OSIS_CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+    <osisText osisIDWork="KJV" osisRefWork="Bible">
+        <header>
+            <work osisWork="KJV">
+                <title>King James Version</title>
+            </work>
+        </header>
+        <div type="book" osisID="Gen">
+            <chapter osisID="Gen.1">
+                <verse osisID="Gen.1.1">In the beginning God created the heaven and the earth.</verse>
+                <verse osisID="Gen.1.2">And the earth was without form, and void; and darkness <transChange type="added">was</transChange> upon the face of the deep.<note type="study">A footnote</note></verse>
+            </chapter>
+        </div>
+        <div type="book" osisID="Matt">
+            <chapter osisID="Matt.4">
+                <verse osisID="Matt.4.19">And he saith unto them, <q who="Jesus" marker="">Follow me, and I will make you fishers of men.</q></verse>
+            </chapter>
+        </div>
+    </osisText>
+</osis>"#;
+
+    const OSIS_MILESTONE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+    <osisText osisIDWork="KJV">
+        <div type="book" osisID="John">
+            <chapter sID="John.11" osisID="John.11"/>
+            <verse sID="John.11.35" osisID="John.11.35"/>Jesus wept.<verse eID="John.11.35"/>
+            <chapter eID="John.11"/>
+            <chapter sID="John.14" osisID="John.14"/>
+            <verse sID="John.14.6" osisID="John.14.6"/>Jesus saith unto him, <q who="Jesus" sID="q1"/>I am the way,<verse eID="John.14.6"/>
+            <verse sID="John.14.7" osisID="John.14.7"/>If ye had known me<q who="Jesus" eID="q1"/>.<verse eID="John.14.7"/>
+            <chapter eID="John.14"/>
+        </div>
+    </osisText>
+</osis>"#;
+
+    fn verse_text(bible: &Bible, book: usize, chapter: usize, verse: usize) -> String {
+        bible.books[book].chapters[chapter].verses[verse].words.iter().map(|w| w.text.as_str()).collect::<String>().trim().to_owned()
+    }
+
+    #[test]
+    fn test_osis_container_structure() {
+        let bible = parse_osis_bible(OSIS_CONTAINER).unwrap();
+        assert_eq!(bible.name, "KJV");
+        assert_eq!(bible.desc, "King James Version");
+        assert_eq!(bible.books.len(), 2);
+        assert_eq!(bible.books[0].name, "Genesis");
+        assert_eq!(bible.books[1].name, "Matthew");
+        assert_eq!((bible.books[0].id.as_str(), bible.books[0].testament), ("GEN", Testament::Old));
+        assert_eq!((bible.books[1].id.as_str(), bible.books[1].testament), ("MAT", Testament::New));
+        assert_eq!(bible.books[0].chapters[0].verses.len(), 2);
+        assert_eq!(verse_text(&bible, 0, 0, 0), "In the beginning God created the heaven and the earth.");
+    }
+
+    #[test]
+    fn test_osis_formatting() {
+        let bible = parse_osis_bible(OSIS_CONTAINER).unwrap();
+
+        let verse = &bible.books[0].chapters[0].verses[1];
+        let italicized = verse.words.iter().filter(|w| w.italicized).map(|w| w.text.trim()).collect::<Vec<_>>();
+        assert_eq!(italicized, vec!["was"]);
+        assert!(!verse.words.iter().any(|w| w.text.contains("footnote")));
+
+        let verse = &bible.books[1].chapters[0].verses[0];
+        assert!(!verse.words[0].red);
+        assert_eq!(verse.words.iter().filter(|w| w.red).count(), 10);
+    }
+
+    #[test]
+    fn test_osis_milestones() {
+        let bible = parse_osis_bible(OSIS_MILESTONE).unwrap();
+        assert_eq!(bible.books.len(), 1);
+        assert_eq!(bible.books[0].chapters.len(), 2);
+        assert_eq!(verse_text(&bible, 0, 0, 0), "Jesus wept.");
+
+        let verse = &bible.books[0].chapters[1].verses[1];
+        assert!(verse.words.iter().take(4).all(|w| w.red));
+    }
+
+    #[test]
+    fn test_osis_matches_text_format() {
+        let osis = parse_osis_bible(OSIS_CONTAINER).unwrap();
+        let text = parse_bible("KJV\n\nGenesis 1:1 In the beginning God created the heaven and the earth.").unwrap();
+
+        let osis_words = osis.books[0].chapters[0].verses[0].words.iter().map(|w| w.text.clone()).collect::<Vec<_>>();
+        let text_words = text.books[0].chapters[0].verses[0].words.iter().map(|w| w.text.clone()).collect::<Vec<_>>();
+        assert_eq!(osis_words, text_words);
+    }
+
+    /// Writes a verse back out in the text format, the inverse of `parse_verse`
+    fn format_verse(verse: &Verse) -> String {
+        let mut text = String::new();
+        let (mut italicized, mut red) = (false, false);
+        for word in &verse.words {
+            let content = word.text.trim_start();
+            let whitespace = &word.text[..word.text.len() - content.len()];
+
+            // whitespace only words have no formatting of their own
+            if content.is_empty() {
+                text.push_str(whitespace);
+                continue;
+            }
+
+            if italicized && !word.italicized { text.push(']'); }
+            if red && !word.red { text.push('}'); }
+            text.push_str(whitespace);
+            if !red && word.red { text.push('{'); }
+            if !italicized && word.italicized { text.push('['); }
+
+            (italicized, red) = (word.italicized, word.red);
+            text.push_str(content);
+
+            word.strongs.iter().for_each(|s| text.push_str(&format!("<{}>", s)));
+            if let Some(morph) = &word.morph { text.push_str(&format!("<({})>", morph)); }
+        }
+
+        if italicized { text.push(']'); }
+        if red { text.push('}'); }
+        text
+    }
+
+    fn format_bible(bible: &Bible) -> String {
+        let mut text = format!("{}\n{}\n", bible.name, bible.desc);
+        for book in &bible.books {
+            for (chapter_index, chapter) in book.chapters.iter().enumerate() {
+                for (verse_index, verse) in chapter.verses.iter().enumerate() {
+                    text.push_str(&format!("{} {}:{}{}\n", book.name, chapter_index + 1, verse_index + 1, format_verse(verse)));
+                }
+            }
+        }
+
+        text
+    }
+
+    fn word_formats(verse: &Verse) -> Vec<(&str, bool, bool)> {
+        verse.words.iter().map(|w| (w.text.as_str(), w.italicized, w.red)).collect()
+    }
+
+    #[test]
+    fn test_red_letter_markup() {
+        let verse = parse_verse(" And he saith unto them, {Follow [me], and I will make you fishers of men.}");
+        let red = verse.words.iter().filter(|w| w.red).map(|w| w.text.trim()).collect::<Vec<_>>();
+        assert_eq!(red, vec!["Follow", "me,", "and", "I", "will", "make", "you", "fishers", "of", "men."]);
+
+        let italicized = verse.words.iter().filter(|w| w.italicized).map(|w| w.text.trim()).collect::<Vec<_>>();
+        assert_eq!(italicized, vec!["me,"]);
+        assert!(verse.words.iter().all(|w| !w.text.contains(['{', '}', '[', ']'])));
+    }
+
+    #[test]
+    fn test_strongs_markup() {
+        let verse = parse_verse(" In the beginning<H7225> God<H430> created<H1254><(8804)> {the} heaven <H8064>");
+        assert_eq!(verse.words.len(), parse_verse(" In the beginning God created {the} heaven").words.len());
+        let tags = verse.words.iter().map(|w| (w.text.trim(), w.strongs.join(" "), w.morph.as_deref())).collect::<Vec<_>>();
+        assert_eq!(tags, [
+            ("In", "".to_owned(), None),
+            ("the", "".to_owned(), None),
+            ("beginning", "H7225".to_owned(), None),
+            ("God", "H430".to_owned(), None),
+            ("created", "H1254".to_owned(), Some("8804")),
+            ("the", "".to_owned(), None),
+            ("heaven", "H8064".to_owned(), None),
+        ]);
+        assert!(verse.words[5].red);
+
+        let reparsed = parse_verse(&format_verse(&verse));
+        assert_eq!(reparsed.words.iter().map(|w| &w.strongs).collect::<Vec<_>>(), verse.words.iter().map(|w| &w.strongs).collect::<Vec<_>>());
+
+        let osis = OSIS_CONTAINER.replace(
+            "In the beginning God created",
+            r#"In the <w lemma="strong:H07225" morph="strongMorph:TH8804">beginning</w> <w lemma="strong:H0430 lemma.TR:elohim">God</w>, created"#
+        );
+        let bible = parse_osis_bible(&osis).unwrap();
+        let words = &bible.books[0].chapters[0].verses[0].words;
+        assert_eq!((words[2].text.as_str(), words[2].strongs.clone(), words[2].morph.as_deref()), (" beginning", vec!["H7225".to_owned()], Some("TH8804")));
+        assert_eq!((words[3].text.as_str(), words[3].strongs.clone()), (" God,", vec!["H430".to_owned()]));
+        assert!(words[4].strongs.is_empty());
+    }
+
+    #[test]
+    fn test_markup_keeps_word_boundaries() {
+        // annotations are stored by word index, so markup must never change how a verse is split into words
+        let plain = parse_verse(" In the  beginning God created the heaven");
+        let marked = parse_verse(" In [the]  {beginning God} [created {the] heaven}");
+
+        let plain_words = plain.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>();
+        let marked_words = marked.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(plain_words, marked_words);
+        assert!(marked.words[1].italicized);
+    }
+
+    #[test]
+    fn test_text_format_round_trip() {
+        let verse = parse_verse(" And he saith, {Come [ye] after me,} and [I  will]{ make you}");
+        assert_eq!(word_formats(&parse_verse(&format_verse(&verse))), word_formats(&verse));
+
+        for src in [include_str!("../../resources/bibles/small_kjv.txt"), include_str!("../../resources/bibles/small_asv.txt")] {
+            let bible = parse_bible(src).unwrap();
+            let reparsed = parse_bible(&format_bible(&bible)).unwrap();
+            assert_eq!(bible.books.len(), reparsed.books.len());
+
+            let verses = bible.books.iter().flat_map(|b| &b.chapters).flat_map(|c| &c.verses);
+            let reparsed_verses = reparsed.books.iter().flat_map(|b| &b.chapters).flat_map(|c| &c.verses);
+            for (verse, reparsed) in verses.zip(reparsed_verses) {
+                assert_eq!(word_formats(verse), word_formats(reparsed));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bundled_red_letter_text() {
+        for src in [include_str!("../../resources/bibles/small_kjv.txt"), include_str!("../../resources/bibles/small_asv.txt")] {
+            let bible = parse_bible(src).unwrap();
+            let mark = bible.books.iter().find(|b| b.name == "Mark").unwrap();
+
+            // Mark 1:17, `And Jesus said unto them, Come ye after me...`
+            let words = mark.chapters[0].verses[16].words.iter().filter(|w| !w.text.trim().is_empty()).collect::<Vec<_>>();
+            assert!(words.iter().take(5).all(|w| !w.red));
+            assert!(words.iter().skip(5).all(|w| w.red));
+        }
+    }
+
+    #[test]
+    fn test_book_ids() {
+        let bible = parse_bible(include_str!("../../resources/bibles/small_sparv.txt")).unwrap();
+        let ids = bible.books.iter().map(|b| b.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["GEN", "NUM", "1SA", "2SA", "PSA", "SNG", "MAL", "MAT", "MRK"]);
+        assert_eq!(bible.books[6].testament, Testament::Old);
+        assert_eq!(bible.books[7].testament, Testament::New);
+
+        let bible = parse_bible("RV\nReina-Valera\nGénesis 1:1 En el principio\n3 Juan 1:1 El anciano\nLibro 1:1 texto\n").unwrap();
+        let ids = bible.books.iter().map(|b| (b.id.as_str(), b.testament)).collect::<Vec<_>>();
+        assert_eq!(ids, [("GEN", Testament::Old), ("3JN", Testament::New), ("LIBRO", Testament::New)]);
+
+        for name in ["II Kings", "2 kings", "2Kgs.", "2ki", "2 Reyes"] {
+            assert_eq!(canon::find_book(name).map(|b| b.id), Some("2KI"), "{}", name);
+        }
+        assert!(canon::find_book("Kings").is_none());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let bible = parse_bible(include_str!("../../resources/bibles/small_kjv.txt")).unwrap();
+
+        let mut data = vec![];
+        cache::encode_bible(&mut data, &bible).unwrap();
+        let decoded = cache::decode_bible(&mut data.as_slice()).unwrap();
+
+        assert_eq!(decoded.name, bible.name);
+        assert_eq!(decoded.desc, bible.desc);
+        assert_eq!(decoded.books.iter().map(|b| &b.name).collect::<Vec<_>>(), bible.books.iter().map(|b| &b.name).collect::<Vec<_>>());
+        assert_eq!(decoded.books.iter().map(|b| (&b.id, b.testament)).collect::<Vec<_>>(), bible.books.iter().map(|b| (&b.id, b.testament)).collect::<Vec<_>>());
+
+        let verses = bible.books.iter().flat_map(|b| &b.chapters).flat_map(|c| &c.verses);
+        let decoded_verses = decoded.books.iter().flat_map(|b| &b.chapters).flat_map(|c| &c.verses);
+        assert_eq!(verses.clone().count(), decoded_verses.clone().count());
+        for (verse, decoded) in verses.zip(decoded_verses) {
+            assert_eq!(word_formats(verse), word_formats(decoded));
+        }
+
+        // truncated and foreign files are rejected rather than partially loaded
+        assert!(cache::decode_bible(&mut &data[..data.len() / 2]).is_err());
+        assert!(cache::decode_bible(&mut "KJV\nKing James Version".as_bytes()).is_err());
+
+        // a corrupt string length gives an error instead of allocating that much
+        let mut corrupt = data[..8].to_vec();
+        corrupt.extend_from_slice(&u32::MAX.to_le_bytes());
+        corrupt.extend_from_slice(b"KJV");
+        assert!(cache::decode_bible(&mut corrupt.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_lazy_bible_loading() {
+        let dir = std::env::temp_dir().join(format!("lazy_bible_test_{}", std::process::id()));
+        let source = dir.join("small_kjv.txt");
+        let cache_dir = dir.join("cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&source, include_str!("../../resources/bibles/small_kjv.txt")).unwrap();
+
+        // the first open parses the source and writes a cache, after which only the header of the cache is read
+        assert!(LazyBible::open(&source, Some(&cache_dir)).unwrap().is_loaded());
+        let mut bible = LazyBible::open(&source, Some(&cache_dir)).unwrap();
+        assert!(!bible.is_loaded());
+
+        // reading does not keep the Bible loaded, getting it does
+        assert_eq!(bible.read().unwrap().name, "KJV");
+        assert!(bible.read_search_index().unwrap().verse_count() > 0);
+        assert!(!bible.is_loaded());
+        assert!(bible.get().is_ok());
+        assert!(bible.is_loaded());
+
+        // a Bible whose cache and source are both gone gives an error, rather than panicking
+        bible.unload();
+        std::fs::remove_file(bible.cache_path().unwrap()).unwrap();
+        std::fs::remove_file(&source).unwrap();
+        assert!(bible.get().is_err());
+        assert!(bible.read().is_err());
+        assert!(bible.get_search_index().is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
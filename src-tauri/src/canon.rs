@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::bible::Testament;
+
+// The books of the Protestant canon, which every book in a Bible is matched against when it is parsed. The id of a
+// book is its USFM code (GEN, EXO, 1SA...), which is the same for every translation, unlike the book names.
+//
+// Books are looked up by their id, OSIS id, English name, or any of the alternate names (the common abbreviations, and
+// the Reina-Valera names used by Spanish translations such as the SpaRV). Names are compared without case, accents,
+// spaces or periods, and a leading roman numeral is read as a number, so `II Kings`, `2 kings` and `2Kgs.` all match
+
+#[derive(Debug, Clone, Copy)]
+pub struct CanonBook
+{
+    pub id: &'static str,
+    pub osis_id: &'static str,
+    pub name: &'static str,
+    pub testament: Testament,
+    pub alt_names: &'static [&'static str],
+}
+
+const fn book(id: &'static str, osis_id: &'static str, name: &'static str, testament: Testament, alt_names: &'static [&'static str]) -> CanonBook
+{
+    CanonBook { id, osis_id, name, testament, alt_names }
+}
+
+use Testament::{New, Old};
+
+/// All the books of the canon, in canonical order
+pub const BOOKS: &[CanonBook] = &[
+    book("GEN", "Gen", "Genesis", Old, &["Ge", "Gn", "Génesis"]),
+    book("EXO", "Exod", "Exodus", Old, &["Ex", "Exo", "Éxodo"]),
+    book("LEV", "Lev", "Leviticus", Old, &["Le", "Lv", "Levítico"]),
+    book("NUM", "Num", "Numbers", Old, &["Nu", "Nm", "Nb", "Números"]),
+    book("DEU", "Deut", "Deuteronomy", Old, &["De", "Dt", "Deuteronomio"]),
+    book("JOS", "Josh", "Joshua", Old, &["Jsh", "Josué"]),
+    book("JDG", "Judg", "Judges", Old, &["Jdgs", "Jg", "Jgs", "Jueces"]),
+    book("RUT", "Ruth", "Ruth", Old, &["Ru", "Rt", "Rth"]),
+    book("1SA", "1Sam", "1 Samuel", Old, &["1 Sm", "1 Sa", "1 S"]),
+    book("2SA", "2Sam", "2 Samuel", Old, &["2 Sm", "2 Sa", "2 S"]),
+    book("1KI", "1Kgs", "1 Kings", Old, &["1 Kgs", "1 Ki", "1 Kin", "1 K", "1 Reyes"]),
+    book("2KI", "2Kgs", "2 Kings", Old, &["2 Kgs", "2 Ki", "2 Kin", "2 K", "2 Reyes"]),
+    book("1CH", "1Chr", "1 Chronicles", Old, &["1 Ch", "1 Chron", "1 Crónicas"]),
+    book("2CH", "2Chr", "2 Chronicles", Old, &["2 Ch", "2 Chron", "2 Crónicas"]),
+    book("EZR", "Ezra", "Ezra", Old, &["Esdras"]),
+    book("NEH", "Neh", "Nehemiah", Old, &["Ne", "Nehemías"]),
+    book("EST", "Esth", "Esther", Old, &["Es", "Ester"]),
+    book("JOB", "Job", "Job", Old, &["Jb"]),
+    book("PSA", "Ps", "Psalms", Old, &["Psalm", "Pss", "Psm", "Pslm", "Salmos"]),
+    book("PRO", "Prov", "Proverbs", Old, &["Pr", "Prv", "Proverbios"]),
+    book("ECC", "Eccl", "Ecclesiastes", Old, &["Ec", "Eccles", "Eccle", "Qoh", "Qoheleth", "Eclesiastés"]),
+    book("SNG", "Song", "Song of Solomon", Old, &["Song of Songs", "Canticles", "Cant", "SoS", "SS", "Cantares", "Cantar de los Cantares"]),
+    book("ISA", "Isa", "Isaiah", Old, &["Is", "Isaías"]),
+    book("JER", "Jer", "Jeremiah", Old, &["Je", "Jr", "Jeremías"]),
+    book("LAM", "Lam", "Lamentations", Old, &["La", "Lamentaciones"]),
+    book("EZK", "Ezek", "Ezekiel", Old, &["Eze", "Ezk", "Ezequiel"]),
+    book("DAN", "Dan", "Daniel", Old, &["Da", "Dn"]),
+    book("HOS", "Hos", "Hosea", Old, &["Ho", "Oseas"]),
+    book("JOL", "Joel", "Joel", Old, &["Jl"]),
+    book("AMO", "Amos", "Amos", Old, &["Am", "Amós"]),
+    book("OBA", "Obad", "Obadiah", Old, &["Ob", "Obd", "Abdías"]),
+    book("JON", "Jonah", "Jonah", Old, &["Jnh", "Jonás"]),
+    book("MIC", "Mic", "Micah", Old, &["Mi", "Mc", "Miqueas"]),
+    book("NAM", "Nah", "Nahum", Old, &["Na", "Nahúm"]),
+    book("HAB", "Hab", "Habakkuk", Old, &["Hb", "Habacuc"]),
+    book("ZEP", "Zeph", "Zephaniah", Old, &["Zp", "Zph", "Sofonías"]),
+    book("HAG", "Hag", "Haggai", Old, &["Hg", "Hageo"]),
+    book("ZEC", "Zech", "Zechariah", Old, &["Zc", "Zch", "Zacarías"]),
+    book("MAL", "Mal", "Malachi", Old, &["Ml", "Malaquías"]),
+    book("MAT", "Matt", "Matthew", New, &["Mt", "Mateo"]),
+    book("MRK", "Mark", "Mark", New, &["Mk", "Mr", "Marcos"]),
+    book("LUK", "Luke", "Luke", New, &["Lk", "Lu", "Lucas"]),
+    book("JHN", "John", "John", New, &["Jn", "Juan"]),
+    book("ACT", "Acts", "Acts", New, &["Ac", "Acts of the Apostles", "Hechos"]),
+    book("ROM", "Rom", "Romans", New, &["Ro", "Rm", "Romanos"]),
+    book("1CO", "1Cor", "1 Corinthians", New, &["1 Co", "1 Corintios"]),
+    book("2CO", "2Cor", "2 Corinthians", New, &["2 Co", "2 Corintios"]),
+    book("GAL", "Gal", "Galatians", New, &["Ga", "Gálatas"]),
+    book("EPH", "Eph", "Ephesians", New, &["Ephes", "Efesios"]),
+    book("PHP", "Phil", "Philippians", New, &["Pp", "Philip", "Filipenses"]),
+    book("COL", "Col", "Colossians", New, &["Colosenses"]),
+    book("1TH", "1Thess", "1 Thessalonians", New, &["1 Th", "1 Thes", "1 Tesalonicenses"]),
+    book("2TH", "2Thess", "2 Thessalonians", New, &["2 Th", "2 Thes", "2 Tesalonicenses"]),
+    book("1TI", "1Tim", "1 Timothy", New, &["1 Ti", "1 Tm", "1 Timoteo"]),
+    book("2TI", "2Tim", "2 Timothy", New, &["2 Ti", "2 Tm", "2 Timoteo"]),
+    book("TIT", "Titus", "Titus", New, &["Ti", "Tito"]),
+    book("PHM", "Phlm", "Philemon", New, &["Pm", "Philem", "Filemón"]),
+    book("HEB", "Heb", "Hebrews", New, &["He", "Hebreos"]),
+    book("JAS", "Jas", "James", New, &["Jm", "Santiago"]),
+    book("1PE", "1Pet", "1 Peter", New, &["1 Pe", "1 Pt", "1 Pedro"]),
+    book("2PE", "2Pet", "2 Peter", New, &["2 Pe", "2 Pt", "2 Pedro"]),
+    book("1JN", "1John", "1 John", New, &["1 Jn", "1 Jo", "1 Jhn", "1 Juan"]),
+    book("2JN", "2John", "2 John", New, &["2 Jn", "2 Jo", "2 Jhn", "2 Juan"]),
+    book("3JN", "3John", "3 John", New, &["3 Jn", "3 Jo", "3 Jhn", "3 Juan"]),
+    book("JUD", "Jude", "Jude", New, &["Jd", "Judas"]),
+    book("REV", "Rev", "Revelation", New, &["Re", "Rv", "Revelations", "Apoc", "Apocalypse", "Apocalipsis"]),
+];
+
+lazy_static::lazy_static!
+{
+    static ref NAME_MAP: HashMap<String, usize> = {
+        let mut map = HashMap::new();
+        for (i, book) in BOOKS.iter().enumerate()
+        {
+            let names = [book.id, book.osis_id, book.name].into_iter().chain(book.alt_names.iter().copied());
+            for name in names
+            {
+                map.entry(normalize_name(name)).or_insert(i);
+            }
+        }
+        map
+    };
+}
+
+/// Gets a book by its id, such as `GEN` or `1SA`
+pub fn get_book(id: &str) -> Option<&'static CanonBook>
+{
+    BOOKS.iter().find(|b| b.id.eq_ignore_ascii_case(id))
+}
+
+/// Gets a book by its OSIS id, such as `Gen` or `1Sam`
+pub fn get_osis_book(osis_id: &str) -> Option<&'static CanonBook>
+{
+    BOOKS.iter().find(|b| b.osis_id == osis_id)
+}
+
+/// Finds a book from any of its names, abbreviations or ids
+pub fn find_book(name: &str) -> Option<&'static CanonBook>
+{
+    NAME_MAP.get(&normalize_name(name)).map(|i| &BOOKS[*i])
+}
+
+/// Gets the position of a book in the canon, so books from different Bibles can be sorted the same way
+pub fn get_book_order(id: &str) -> Option<usize>
+{
+    BOOKS.iter().position(|b| b.id.eq_ignore_ascii_case(id))
+}
+
+/// Lowercases a name, removing its accents, spaces and periods, and reading a leading roman numeral as a number
+pub fn normalize_name(name: &str) -> String
+{
+    let name = name.trim();
+    let (prefix, rest) = match name.split_once(char::is_whitespace)
+    {
+        Some((first, rest)) if !rest.trim().is_empty() => match first.to_ascii_lowercase().as_str()
+        {
+            "i" => ("1", rest),
+            "ii" => ("2", rest),
+            "iii" => ("3", rest),
+            _ => ("", name),
+        },
+        _ => ("", name),
+    };
+
+    prefix.chars()
+        .chain(rest.chars())
+        .flat_map(char::to_lowercase)
+        .map(fold_accent)
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+fn fold_accent(c: char) -> char
+{
+    match c
+    {
+        'á' | 'à' | 'â' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ñ' => 'n',
+        _ => c,
+    }
+}
@@ -1,11 +1,11 @@
-use std::str::FromStr;
+use std::{path::Path, str::FromStr, sync::Arc};
 
 use itertools::Itertools;
 use tauri::{path::BaseDirectory, Manager, Runtime, State};
 use uuid::Uuid;
 
 use crate::{
-    app_state::{self, AppState, ViewState}, audio::reader_behavior::ReaderBehavior, bible::{ChapterIndex, ReferenceLocation, Verse}, notes::{action::ActionType, HighlightCategory, NoteData, NoteSourceType}, searching::{self, *}, settings::Settings, utils::Color
+    app_state::{self, AppState, ViewState}, audio::reader_behavior::ReaderBehavior, bible::{ChapterIndex, ReferenceLocation, Verse, VerseRange, WordStrongs}, comparison::{self, ChapterComparison, ChapterDiff}, notes::{action::ActionType, search::{NoteSearchResult, NotebookQuery}, HighlightCategory, NoteData, NoteSourceType}, prompt, searching::{self, history::SavedSearch, versions::VersionSearch, *}, settings::Settings, utils::Color
 };
 
 #[tauri::command(rename_all = "snake_case")]
@@ -104,17 +104,57 @@ pub fn clear_view_states(app_state: State<'_, AppState>)
 #[tauri::command(rename_all = "snake_case")]
 pub fn get_chapter_text(app_state: State<'_, AppState>, chapter: ChapterIndex) -> String 
 {
-    let app_state = app_state.get_ref();
-    let chapter = &app_state.get_current_bible().books[chapter.book as usize].chapters[chapter.number as usize];
+    let bible = app_state.get_ref().get_current_bible();
+    let chapter = &bible.books[chapter.book as usize].chapters[chapter.number as usize];
     serde_json::to_string(chapter).unwrap()
 }
 
+/// Gets the chapter, or the verses in it, from the current version, aligned with the same verses in each of the `versions`
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_chapter_comparison(app_state: State<'_, AppState>, chapter: ChapterIndex, verse_range: Option<VerseRange>, versions: Vec<String>) -> Option<ChapterComparison>
+{
+    let app_state = app_state.get_ref();
+    let bible = app_state.get_current_bible();
+
+    // versions that are not installed are left out of the comparison
+    let versions = versions.iter().filter_map(|v| app_state.get_bible(v)).collect_vec();
+    let versions = versions.iter().map(|b| b.as_ref()).collect_vec();
+    comparison::compare_chapter(&bible, chapter, verse_range, &versions)
+}
+
+/// Diffs the words of the chapter, or the verses in it, between the `from` and `to` versions. The chapter is in the numbering of `from`
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_chapter_diff(app_state: State<'_, AppState>, chapter: ChapterIndex, verse_range: Option<VerseRange>, from: String, to: String) -> Option<ChapterDiff>
+{
+    let app_state = app_state.get_ref();
+    let from = app_state.get_bible(&from)?;
+    let to = app_state.get_bible(&to)?;
+    comparison::diff_chapter(&from, &to, chapter, verse_range)
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn get_verse(app_state: State<'_, AppState>, book: u32, chapter: u32, verse: u32) -> Verse {
     app_state.get_ref().get_current_bible().books[book as usize].chapters[chapter as usize].verses[verse as usize]
         .clone()
 }
 
+/// Gets the Strong's numbers and morphology of a word in the current version, if the word exists
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_word_strongs(app_state: State<'_, AppState>, chapter: ChapterIndex, verse: u32, word: u32) -> Option<WordStrongs>
+{
+    let bible = app_state.get_ref().get_current_bible();
+    let word = bible.books.get(chapter.book as usize)?
+        .chapters.get(chapter.number as usize)?
+        .verses.get(verse as usize)?
+        .words.get(word as usize)?;
+
+    Some(WordStrongs {
+        text: word.text.trim().to_owned(),
+        strongs: word.strongs.clone(),
+        morph: word.morph.clone(),
+    })
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn get_book_name(app_state: State<'_, AppState>, book: u32) -> String {
     app_state.get_ref().get_current_bible().books[book as usize].name.clone()
@@ -225,17 +265,138 @@ pub fn erase_location_highlight(app_state: State<'_, AppState>, location: Refere
 
 #[tauri::command(rename_all = "snake_case")]
 pub fn parse_bible_search(app_state: State<'_, AppState>, text: &str) -> ParsedSearchResult {
-    let app_state = app_state.get_ref();
-    let bible = app_state.get_current_bible();
-    parse_search(text, bible)
+    let bible = app_state.get_ref().get_current_bible();
+    parse_search(text, &bible)
 }
 
+/// Gets the pinned, saved and recent searches, in that order
 #[tauri::command(rename_all = "snake_case")]
-pub fn run_word_search(app_state: State<'_, AppState>, words: Vec<String>) -> Vec<WordSearchResult> {
+pub fn get_search_history(app_state: State<'_, AppState>) -> Vec<SavedSearch>
+{
+    app_state.get_ref().read_search_history(|h| h.get_searches())
+}
+
+/// Adds a search from the search bar to the history
+#[tauri::command(rename_all = "snake_case")]
+pub fn add_search_to_history(app_state: State<'_, AppState>, text: &str)
+{
+    app_state.get_ref().read_search_history(|h| h.add_recent(text))
+}
+
+/// Saves a search with a name, returning its id
+#[tauri::command(rename_all = "snake_case")]
+pub fn save_search(app_state: State<'_, AppState>, text: &str, name: &str) -> String
+{
+    app_state.get_ref().read_search_history(|h| h.save(text, name))
+}
+
+/// Parses a saved or recent search again, in the current version. Returns None if there is no such search
+#[tauri::command(rename_all = "snake_case")]
+pub fn rerun_search(app_state: State<'_, AppState>, id: &str) -> Option<ParsedSearchResult>
+{
     let app_state = app_state.get_ref();
-    let bible = app_state.get_current_bible();
+    let text = app_state.read_search_history(|h| h.rerun(id))?;
+    Some(parse_search(&text, &app_state.get_current_bible()))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_search_pinned(app_state: State<'_, AppState>, id: &str, pinned: bool) -> bool
+{
+    app_state.get_ref().read_search_history(|h| h.set_pinned(id, pinned))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn delete_search(app_state: State<'_, AppState>, id: &str) -> bool
+{
+    app_state.get_ref().read_search_history(|h| h.remove(id))
+}
+
+/// Forgets every recent search that is not pinned or saved
+#[tauri::command(rename_all = "snake_case")]
+pub fn clear_search_history(app_state: State<'_, AppState>)
+{
+    app_state.get_ref().read_search_history(|h| h.clear_recent())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn run_word_search(app_state: State<'_, AppState>, words: Vec<String>, order: Option<SearchOrder>) -> Vec<WordSearchResult> {
+    let app_ref = app_state.get_ref();
+    let bible = app_ref.get_current_bible();
+    let index = app_ref.get_current_search_index();
+    drop(app_ref); // a pattern search can take a while, so don't block other commands during it
+
     let words = words.iter().map(|w| w.as_str()).collect_vec();
-    search_bible(&words, bible)
+    let mut results = run_search(&words, &bible, &index);
+    sort_results(&mut results, order.unwrap_or_default());
+    results
+}
+
+/// Runs a search in every installed version, giving the versions that matched each verse, which is in the numbering of the current version,
+/// and the versions whose pattern search was cut short.
+/// Every version is loaded to be searched, but only the current version is kept in memory afterwards
+#[tauri::command(rename_all = "snake_case")]
+pub fn run_version_search(app_state: State<'_, AppState>, words: Vec<String>) -> VersionSearch {
+    let app_ref = app_state.get_ref();
+    let current = app_ref.get_current_bible_version();
+    let current_bible = app_ref.get_current_bible();
+
+    let versions = app_ref.get_bibles().into_iter()
+        .sorted()
+        .filter_map(|name| {
+            let bible = app_ref.get_bible(&name)?;
+
+            // the index of the current version is kept, any other is built from the Bible that was just read
+            let index = if name == current { app_ref.get_current_search_index() } else { Arc::new(SearchIndex::new(&bible)) };
+
+            Some((bible, index))
+        })
+        .collect_vec();
+    drop(app_ref); // the pattern searches can take a while, so don't block other commands during them
+
+    let words = words.iter().map(|w| w.as_str()).collect_vec();
+    let versions = versions.iter().map(|(bible, index)| (bible.as_ref(), index.as_ref())).collect_vec();
+    searching::versions::search_versions(&words, &current_bible, &versions)
+}
+
+/// Counts the uses of a word or phrase in each book and chapter of a version, or the current version if none is given.
+/// Returns None if the version is not installed, or the text has no words
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_word_concordance(app_state: State<'_, AppState>, text: &str, version: Option<String>) -> Option<Concordance>
+{
+    let app_state = app_state.get_ref();
+    let index = match version
+    {
+        Some(version) => app_state.get_search_index(&version)?,
+        None => app_state.get_current_search_index(),
+    };
+
+    get_concordance(text, &index).ok()
+}
+
+/// Gets the most used words in a passage of a version, or the current version if none is given. Returns None if the version is not installed
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_passage_word_frequencies(app_state: State<'_, AppState>, passage: SectionSearchResult, count: u32, ignore_common: bool, version: Option<String>) -> Option<Vec<WordFrequency>>
+{
+    let app_state = app_state.get_ref();
+    let bible = match version
+    {
+        Some(version) => app_state.get_bible(&version)?,
+        None => app_state.get_current_bible(),
+    };
+
+    Some(get_word_frequencies(&bible, &passage, count as usize, ignore_common))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn search_notebook(app_state: State<'_, AppState>, query: NotebookQuery) -> Vec<NoteSearchResult> {
+    app_state.get_ref().read_current_notebook(|notebook| notebook.search_notes(&query))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn search_highlight_categories(app_state: State<'_, AppState>, text: &str) -> Vec<HighlightCategory> {
+    app_state.get_ref().read_current_notebook(|notebook| {
+        notebook.search_highlight_categories(text).into_iter().cloned().collect()
+    })
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -407,8 +568,21 @@ pub fn set_current_bible_version(app_state: State<'_, AppState>, version: String
 pub fn get_bible_versions(app_state: State<'_, AppState>) -> Vec<String>
 {
     app_state.get_ref().get_bibles()
-        .map(|v| v.clone())
-        .collect()
+}
+
+/// Imports a Bible file picked by the user, returning the name of the new version. Any errors are shown to the user
+#[tauri::command(rename_all = "snake_case")]
+pub fn import_bible<R>(app: tauri::AppHandle<R>, app_state: State<'_, AppState>, path: String) -> Option<String>
+    where R : Runtime
+{
+    match app_state.get_ref().import_bible(app.path(), Path::new(&path))
+    {
+        Ok(name) => Some(name),
+        Err(err) => {
+            prompt::notify_user(app, "Failed to Import Bible".into(), err);
+            None
+        }
+    }
 }
 
 #[tauri::command(rename_all = "snake_case")]
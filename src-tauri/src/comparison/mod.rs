@@ -0,0 +1,193 @@
+mod test;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bible::{Bible, ChapterIndex, Verse, VerseRange, Word};
+
+// Compares the same passage across several versions. The passage is given in the numbering of a base version, and
+// each verse is looked up in the other versions by the book's canonical id and the versions' versifications, so the
+// verses stay aligned even when the books are in a different order, or the chapters and verses are numbered differently.
+// Two versions can also be diffed word by word, which uses the same alignment
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparedVerse {
+    /// Where the verse is in its version, as it may be numbered differently than in the base version
+    pub chapter: ChapterIndex,
+    pub verse: u32,
+    pub text: Verse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerseComparison {
+    /// The index of the verse in the base version
+    pub verse: u32,
+    /// The verse in each of the compared versions, in the same order as `ChapterComparison::versions`.
+    /// `None` if the version does not have the verse
+    pub verses: Vec<Option<ComparedVerse>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterComparison {
+    pub versions: Vec<String>,
+    pub verses: Vec<VerseComparison>,
+}
+
+/// Compares a chapter, or a range of verses in it, across `versions`. Returns `None` if the chapter or verses do not
+/// exist in the `base` version
+pub fn compare_chapter(base: &Bible, chapter: ChapterIndex, verse_range: Option<VerseRange>, versions: &[&Bible]) -> Option<ChapterComparison>
+{
+    let book = base.books.get(chapter.book as usize)?;
+    let verse_count = book.chapters.get(chapter.number as usize)?.verses.len() as u32;
+
+    let range = verse_range.unwrap_or(VerseRange { start: 0, end: verse_count.saturating_sub(1) });
+    if range.start > range.end || range.end >= verse_count
+    {
+        return None;
+    }
+
+    let verses = (range.start..=range.end).map(|verse| VerseComparison {
+        verse,
+        verses: versions.iter().map(|other| find_verse(base, chapter, verse, other)).collect(),
+    }).collect();
+
+    Some(ChapterComparison {
+        versions: versions.iter().map(|b| b.name.clone()).collect(),
+        verses,
+    })
+}
+
+/// Finds the verse in `other` that is the same as the verse in `base`
+pub fn find_verse(base: &Bible, chapter: ChapterIndex, verse: u32, other: &Bible) -> Option<ComparedVerse>
+{
+    let id = &base.books.get(chapter.book as usize)?.id;
+    let book = other.get_book_index(id)?;
+
+    let (chapter_number, verse_number) = base.versification.map_verse(other.versification, id, chapter.number + 1, verse + 1);
+    let chapter = ChapterIndex { book, number: chapter_number.checked_sub(1)? };
+    let verse = verse_number.checked_sub(1)?;
+
+    let text = other.books[book as usize].chapters
+        .get(chapter.number as usize)?
+        .verses
+        .get(verse as usize)?
+        .clone();
+
+    Some(ComparedVerse { chapter, verse, text })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    /// The words are in both versions
+    Equal,
+    /// The words are only in the `to` version
+    Insert,
+    /// The words are only in the `from` version
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSpan {
+    pub kind: DiffKind,
+    /// The index of the first word in the verse it is from. Deleted words are from the `from` verse, all others from the `to` verse
+    pub word_start: u32,
+    pub words: Vec<Word>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerseDiff {
+    /// The index of the verse in the `from` version
+    pub verse: u32,
+    /// The verse in the `to` version, or `None` if it does not have the verse, in which case every word is deleted
+    pub to_verse: Option<ComparedVerse>,
+    pub spans: Vec<DiffSpan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterDiff {
+    pub from: String,
+    pub to: String,
+    pub verses: Vec<VerseDiff>,
+}
+
+/// Diffs the words of a chapter, or a range of verses in it, between two versions. The chapter and verses are in the
+/// numbering of the `from` version. Returns `None` if they do not exist in the `from` version
+pub fn diff_chapter(from: &Bible, to: &Bible, chapter: ChapterIndex, verse_range: Option<VerseRange>) -> Option<ChapterDiff>
+{
+    let comparison = compare_chapter(from, chapter, verse_range, &[to])?;
+    let from_chapter = from.get_chapter(chapter);
+
+    let verses = comparison.verses.into_iter().map(|mut v| {
+        let to_verse = v.verses.pop().flatten();
+        let from_words = &from_chapter.verses[v.verse as usize].words;
+        let to_words = to_verse.as_ref().map_or(&[] as &[Word], |t| &t.text.words);
+
+        VerseDiff {
+            verse: v.verse,
+            spans: diff_words(from_words, to_words),
+            to_verse,
+        }
+    }).collect();
+
+    Some(ChapterDiff {
+        from: from.name.clone(),
+        to: to.name.clone(),
+        verses,
+    })
+}
+
+/// Diffs two lists of words, using the longest common subsequence of the words. Words are compared by their text
+/// without the surrounding whitespace, so formatting (italics, red letters) is not counted as a difference
+pub fn diff_words(from: &[Word], to: &[Word]) -> Vec<DiffSpan>
+{
+    let is_equal = |f: usize, t: usize| from[f].text.trim() == to[t].text.trim();
+
+    // lengths[f][t] is the length of the longest common subsequence of `from[f..]` and `to[t..]`
+    let mut lengths = vec![vec![0u32; to.len() + 1]; from.len() + 1];
+    for f in (0..from.len()).rev()
+    {
+        for t in (0..to.len()).rev()
+        {
+            lengths[f][t] = if is_equal(f, t)
+            {
+                lengths[f + 1][t + 1] + 1
+            }
+            else
+            {
+                lengths[f + 1][t].max(lengths[f][t + 1])
+            };
+        }
+    }
+
+    let mut spans: Vec<DiffSpan> = vec![];
+    let mut push = |kind: DiffKind, index: usize, word: &Word| {
+        match spans.last_mut()
+        {
+            Some(span) if span.kind == kind => span.words.push(word.clone()),
+            _ => spans.push(DiffSpan { kind, word_start: index as u32, words: vec![word.clone()] }),
+        }
+    };
+
+    let (mut f, mut t) = (0, 0);
+    while f < from.len() || t < to.len()
+    {
+        if f < from.len() && t < to.len() && is_equal(f, t)
+        {
+            push(DiffKind::Equal, t, &to[t]);
+            f += 1;
+            t += 1;
+        }
+        else if f < from.len() && (t == to.len() || lengths[f + 1][t] >= lengths[f][t + 1])
+        {
+            push(DiffKind::Delete, f, &from[f]);
+            f += 1;
+        }
+        else
+        {
+            push(DiffKind::Insert, t, &to[t]);
+            t += 1;
+        }
+    }
+
+    spans
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests
+{
+    use crate::bible::{Bible, Book, Chapter, ChapterIndex, Testament, Verse, VerseRange, Word};
+    use crate::comparison::{compare_chapter, diff_chapter, diff_words, DiffKind, DiffSpan};
+    use crate::versification::Versification;
+
+    fn words(text: &str) -> Vec<Word>
+    {
+        text.split(' ').enumerate().map(|(i, w)| Word {
+            text: if i == 0 { w.to_owned() } else { format!(" {}", w) },
+            italicized: false,
+            red: false,
+            strongs: vec![],
+            morph: None,
+        }).collect()
+    }
+
+    fn create_bible(name: &str, book_name: &str, verses: &[&str]) -> Bible
+    {
+        let chapter = Chapter { verses: verses.iter().map(|v| Verse { words: words(v) }).collect() };
+        Bible {
+            name: name.to_owned(),
+            desc: String::new(),
+            books: vec![Book { name: book_name.to_owned(), id: "JHN".to_owned(), testament: Testament::New, chapters: vec![chapter] }],
+            versification: Versification::Kjv,
+        }
+    }
+
+    fn create_psalms(name: &str, versification: Versification, chapter_count: u32) -> Bible
+    {
+        let chapters = (1..=chapter_count).map(|c| Chapter {
+            verses: (1..=3).map(|v| Verse { words: words(&format!("{} {}:{}", name, c, v)) }).collect(),
+        }).collect();
+
+        Bible {
+            name: name.to_owned(),
+            desc: String::new(),
+            books: vec![Book { name: "Psalms".to_owned(), id: "PSA".to_owned(), testament: Testament::Old, chapters }],
+            versification,
+        }
+    }
+
+    fn span_text(span: &DiffSpan) -> (DiffKind, u32, String)
+    {
+        (span.kind, span.word_start, span.words.iter().map(|w| w.text.as_str()).collect::<String>().trim().to_owned())
+    }
+
+    #[test]
+    fn test_diff_words()
+    {
+        let from = words("For God so loved the world");
+        let to = words("For God loved the whole world");
+
+        let spans = diff_words(&from, &to).iter().map(span_text).collect::<Vec<_>>();
+        assert_eq!(spans, [
+            (DiffKind::Equal, 0, "For God".to_owned()),
+            (DiffKind::Delete, 2, "so".to_owned()),
+            (DiffKind::Equal, 2, "loved the".to_owned()),
+            (DiffKind::Insert, 4, "whole".to_owned()),
+            (DiffKind::Equal, 5, "world".to_owned()),
+        ]);
+
+        // a replaced word is deleted, then inserted
+        let spans = diff_words(&words("Jesus wept"), &words("Jesus cried")).iter().map(span_text).collect::<Vec<_>>();
+        assert_eq!(spans[1..], [(DiffKind::Delete, 1, "wept".to_owned()), (DiffKind::Insert, 1, "cried".to_owned())]);
+        assert!(diff_words(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_compare_chapter()
+    {
+        let kjv = create_bible("KJV", "John", &["In the beginning was the Word", "The same was in the beginning with God"]);
+        let rv = create_bible("RV", "Juan", &["En el principio era el Verbo"]);
+
+        // books are matched by their id, not their name
+        let comparison = compare_chapter(&kjv, ChapterIndex { book: 0, number: 0 }, None, &[&rv]).unwrap();
+        assert_eq!(comparison.versions, ["RV"]);
+        assert_eq!(comparison.verses.iter().map(|v| v.verse).collect::<Vec<_>>(), [0, 1]);
+        assert!(comparison.verses[0].verses[0].is_some());
+        assert!(comparison.verses[1].verses[0].is_none());
+
+        let comparison = compare_chapter(&kjv, ChapterIndex { book: 0, number: 0 }, Some(VerseRange { start: 1, end: 1 }), &[&kjv, &rv]).unwrap();
+        assert_eq!(comparison.verses.len(), 1);
+        assert_eq!(comparison.verses[0].verses.iter().map(|v| v.is_some()).collect::<Vec<_>>(), [true, false]);
+
+        assert!(compare_chapter(&kjv, ChapterIndex { book: 0, number: 1 }, None, &[&rv]).is_none());
+        assert!(compare_chapter(&kjv, ChapterIndex { book: 0, number: 0 }, Some(VerseRange { start: 1, end: 2 }), &[&rv]).is_none());
+
+        // Psalm 11 in the KJV is Psalm 10 in the Vulgate
+        let kjv = create_psalms("KJV", Versification::Kjv, 11);
+        let vulgate = create_psalms("VULG", Versification::Vulgate, 10);
+
+        let comparison = compare_chapter(&kjv, ChapterIndex { book: 0, number: 10 }, None, &[&vulgate]).unwrap();
+        let verse = comparison.verses[1].verses[0].as_ref().unwrap();
+        assert_eq!((verse.chapter, verse.verse), (ChapterIndex { book: 0, number: 9 }, 1));
+        assert_eq!(verse.text.words.iter().map(|w| w.text.as_str()).collect::<String>(), "VULG 10:2");
+    }
+
+    #[test]
+    fn test_diff_chapter()
+    {
+        let kjv = create_bible("KJV", "John", &["In the beginning was the Word", "The same was in the beginning with God"]);
+        let rv = create_bible("RV", "Juan", &["En el principio era el Verbo"]);
+
+        // a verse missing from the other version is deleted entirely
+        let range = Some(VerseRange { start: 1, end: 1 });
+        let diff = diff_chapter(&kjv, &rv, ChapterIndex { book: 0, number: 0 }, range).unwrap();
+        assert_eq!(diff.verses.len(), 1);
+        assert!(diff.verses[0].to_verse.is_none());
+        assert_eq!(diff.verses[0].spans.iter().map(span_text).collect::<Vec<_>>(), [(DiffKind::Delete, 0, "The same was in the beginning with God".to_owned())]);
+
+        assert!(diff_chapter(&kjv, &rv, ChapterIndex { book: 0, number: 1 }, None).is_none());
+    }
+}
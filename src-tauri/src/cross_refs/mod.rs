@@ -0,0 +1,187 @@
+mod test;
+
+use std::{collections::HashMap, fs};
+
+use tauri::{path::{BaseDirectory, PathResolver}, Runtime, State};
+
+use crate::{app_state::AppState, bible::{Bible, ChapterIndex, VerseRange}, canon, debug_release_val, searching::SectionSearchResult};
+
+// Cross references in the format of the OpenBible.info dataset (derived from the Treasury of Scripture Knowledge),
+// one reference per line, with OSIS references in the KJV versification, and the number of votes for how related they are:
+//   From Verse	To Verse	Votes
+//   Gen.1.1	John.1.1-John.1.3	363
+
+pub const CROSS_REFERENCES_PATH: &str = debug_release_val! {
+    debug: "resources/cross_references/small_cross_references.txt",
+    release: "resources/cross_references/cross_references.txt",
+};
+
+/// A verse in the KJV versification, by the canonical id of its book. Chapters and verses are 1 based
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CanonVerse
+{
+    pub book: &'static str,
+    pub chapter: u32,
+    pub verse: u32,
+}
+
+/// A passage related to a verse. The passage is in one chapter, as ranges across chapters are cut off at the end of the first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossReference
+{
+    pub book: &'static str,
+    pub chapter: u32,
+    pub verse_start: u32,
+    /// None if the range goes on past the end of the chapter
+    pub verse_end: Option<u32>,
+    pub votes: u32,
+}
+
+pub struct CrossReferenceDatabase
+{
+    references: HashMap<CanonVerse, Vec<CrossReference>>,
+}
+
+impl CrossReferenceDatabase
+{
+    /// Any lines of the dataset that can not be parsed are skipped and logged. If the dataset can not be read, there are no references
+    pub fn new<R>(resolver: &PathResolver<R>) -> Self 
+        where R : Runtime
+    {
+        let data = resolver.resolve(CROSS_REFERENCES_PATH, BaseDirectory::Resource)
+            .map_err(|e| e.to_string())
+            .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()));
+
+        let data = data.unwrap_or_else(|err| {
+            println!("ERROR: Failed to read the cross references `{}`: {}", CROSS_REFERENCES_PATH, err);
+            String::new()
+        });
+
+        let (database, errors) = Self::parse(&data);
+        for err in &errors
+        {
+            println!("ERROR: Skipped a cross reference: {}", err);
+        }
+
+        database
+    }
+
+    /// References with no votes (or negative votes, meaning they were voted as unrelated) are not kept. Each verse's
+    /// references are sorted by their votes, most first. Lines that are not valid are skipped, and an error for each is returned
+    pub fn parse(text: &str) -> (Self, Vec<String>)
+    {
+        let mut references: HashMap<CanonVerse, Vec<CrossReference>> = HashMap::new();
+        let mut errors = vec![];
+        for (index, line) in text.lines().enumerate()
+        {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("From Verse") { continue; }
+
+            match parse_line(line)
+            {
+                Ok(Some((from, reference))) => references.entry(from).or_default().push(reference),
+                Ok(None) => {},
+                Err(err) => errors.push(format!("Line {}: {}", index + 1, err)),
+            }
+        }
+
+        references.values_mut().for_each(|r| r.sort_by_key(|r| std::cmp::Reverse(r.votes)));
+        (Self { references }, errors)
+    }
+
+    pub fn get_references(&self, verse: &CanonVerse) -> &[CrossReference]
+    {
+        self.references.get(verse).map_or(&[], |r| r.as_slice())
+    }
+
+    /// Gets the passages related to a verse in `bible`, using its book order and versification. References to books
+    /// or verses that the Bible does not have are left out
+    pub fn get_sections(&self, bible: &Bible, chapter: ChapterIndex, verse: u32) -> Vec<SectionSearchResult>
+    {
+        let Some(book) = bible.books.get(chapter.book as usize).and_then(|b| canon::get_book(&b.id)) else {
+            return vec![];
+        };
+
+        let (chapter_number, verse_number) = bible.versification.to_standard(book.id, chapter.number + 1, verse + 1);
+        let verse = CanonVerse { book: book.id, chapter: chapter_number, verse: verse_number };
+
+        self.get_references(&verse).iter().filter_map(|r| {
+            let book = bible.get_book_index(r.book)?;
+            let (chapter, verse_start) = bible.versification.from_standard(r.book, r.chapter, r.verse_start);
+            let verse_end = r.verse_end.map(|v| bible.versification.from_standard(r.book, r.chapter, v));
+
+            let chapter = chapter.checked_sub(1)?;
+            let verse_count = bible.books[book as usize].chapters.get(chapter as usize)?.verses.len() as u32;
+            let start = verse_start.checked_sub(1).filter(|v| *v < verse_count)?;
+            let end = match verse_end
+            {
+                Some((end_chapter, verse_end)) if end_chapter == chapter + 1 => verse_end.saturating_sub(1).min(verse_count - 1),
+                _ => verse_count - 1,
+            };
+
+            Some(SectionSearchResult {
+                book,
+                chapter,
+                verse_range: Some(VerseRange { start, end: end.max(start) }),
+            })
+        }).collect()
+    }
+}
+
+/// Parses a line of the dataset, giving None for a reference without any votes
+fn parse_line(line: &str) -> Result<Option<(CanonVerse, CrossReference)>, String>
+{
+    let mut columns = line.split('\t');
+    let (Some(from), Some(to), Some(votes)) = (columns.next(), columns.next(), columns.next()) else {
+        return Err("expected a from verse, to verse and votes".into());
+    };
+
+    let votes: i32 = votes.trim().parse().map_err(|_| format!("invalid votes `{}`", votes))?;
+    if votes <= 0 { return Ok(None); }
+
+    let Some(from) = parse_osis_range(from) else {
+        return Err(format!("invalid verse `{}`", from));
+    };
+
+    let Some(to) = parse_osis_range(to) else {
+        return Err(format!("invalid verse `{}`", to));
+    };
+
+    let same_chapter = to.1.book == to.0.book && to.1.chapter == to.0.chapter;
+    Ok(Some((from.0, CrossReference {
+        book: to.0.book,
+        chapter: to.0.chapter,
+        verse_start: to.0.verse,
+        verse_end: same_chapter.then(|| to.1.verse.max(to.0.verse)),
+        votes: votes as u32,
+    })))
+}
+
+/// Parses a reference such as `Gen.1.1` or `Gen.1.1-Gen.1.3`, returning the start and end verses
+fn parse_osis_range(reference: &str) -> Option<(CanonVerse, CanonVerse)>
+{
+    let (start, end) = reference.trim().split_once('-').unwrap_or((reference.trim(), reference.trim()));
+    Some((parse_osis_verse(start)?, parse_osis_verse(end)?))
+}
+
+fn parse_osis_verse(reference: &str) -> Option<CanonVerse>
+{
+    let mut parts = reference.split('.');
+    let (Some(book), Some(chapter), Some(verse), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return None;
+    };
+
+    Some(CanonVerse {
+        book: canon::get_osis_book(book)?.id,
+        chapter: chapter.parse().ok()?,
+        verse: verse.parse().ok()?,
+    })
+}
+
+/// Gets the passages related to a verse in the current version, most related first
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_cross_references(state: State<'_, CrossReferenceDatabase>, app_state: State<'_, AppState>, chapter: ChapterIndex, verse: u32) -> Vec<SectionSearchResult>
+{
+    let bible = app_state.get_ref().get_current_bible();
+    state.get_sections(&bible, chapter, verse)
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests
+{
+    use crate::bible::{Bible, Book, Chapter, ChapterIndex, Testament, Verse};
+    use crate::cross_refs::{CanonVerse, CrossReferenceDatabase};
+    use crate::versification::Versification;
+
+    fn load_references() -> CrossReferenceDatabase
+    {
+        CrossReferenceDatabase::parse(include_str!("../../resources/cross_references/small_cross_references.txt")).0
+    }
+
+    fn create_book(id: &str, chapter_lengths: &[usize]) -> Book
+    {
+        Book {
+            name: id.to_owned(),
+            id: id.to_owned(),
+            testament: if id == "JHN" { Testament::New } else { Testament::Old },
+            chapters: chapter_lengths.iter().map(|len| Chapter { verses: vec![Verse { words: vec![] }; *len] }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_get_references()
+    {
+        let references = load_references();
+        let genesis = references.get_references(&CanonVerse { book: "GEN", chapter: 1, verse: 1 });
+
+        assert_eq!(genesis.len(), 8);
+        assert!(genesis.windows(2).all(|r| r[0].votes >= r[1].votes));
+        assert_eq!((genesis[0].book, genesis[0].chapter, genesis[0].verse_start), ("HEB", 11, 3));
+
+        // negatively voted references are left out
+        assert!(genesis.iter().all(|r| r.book != "MRK"));
+
+        let john = genesis.iter().find(|r| r.book == "JHN").unwrap();
+        assert_eq!((john.chapter, john.verse_start, john.verse_end, john.votes), (1, 1, Some(3), 363));
+
+        assert!(references.get_references(&CanonVerse { book: "GEN", chapter: 50, verse: 1 }).is_empty());
+
+        // invalid lines are skipped, without losing the rest of the references
+        let (references, errors) = CrossReferenceDatabase::parse("Gen.1.1\tNotABook.1.1\t5\nGen.1.1\tJohn.1.1\tmany\nGen.1.1\tJohn.1.1\t5");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Line 1:"));
+        assert_eq!(references.get_references(&CanonVerse { book: "GEN", chapter: 1, verse: 1 }).len(), 1);
+    }
+
+    #[test]
+    fn test_get_sections()
+    {
+        let references = load_references();
+
+        // a Bible with only some of the referenced books, numbering the Psalms like the Vulgate
+        let bible = Bible {
+            name: "Test".to_owned(),
+            desc: String::new(),
+            books: vec![create_book("GEN", &[31]), create_book("PSA", &[10; 40]), create_book("JHN", &[51])],
+            versification: Versification::Vulgate,
+        };
+
+        let sections = references.get_sections(&bible, ChapterIndex { book: 0, number: 0 }, 0);
+        let sections = sections.iter().map(|s| (s.book, s.chapter, s.verse_range.map(|r| (r.start, r.end)))).collect::<Vec<_>>();
+
+        assert_eq!(sections, vec![
+            (2, 0, Some((0, 2))), // John 1:1-3
+            (1, 31, Some((5, 5))), // Psalm 33:6, which is 32:6 in the Vulgate
+            (1, 7, Some((2, 2))), // Psalm 8:3
+        ]);
+
+        assert!(references.get_sections(&bible, ChapterIndex { book: 5, number: 0 }, 0).is_empty());
+    }
+
+    #[test]
+    fn test_get_sections_across_chapters()
+    {
+        // Psalm 10:17 is the end of Psalm 9 in the Vulgate, which numbers the verses of Psalm 10 from 21
+        let references = CrossReferenceDatabase::parse("Gen.1.1\tPs.10.17-Ps.11.2\t10\nGen.1.1\tPs.10.3-Ps.10.5\t5").0;
+        assert_eq!(references.get_references(&CanonVerse { book: "GEN", chapter: 1, verse: 1 })[0].verse_end, None);
+
+        let bible = Bible {
+            name: "Test".to_owned(),
+            desc: String::new(),
+            books: vec![create_book("GEN", &[31]), create_book("PSA", &[40; 20])],
+            versification: Versification::Vulgate,
+        };
+
+        let sections = references.get_sections(&bible, ChapterIndex { book: 0, number: 0 }, 0);
+        let sections = sections.iter().map(|s| (s.book, s.chapter, s.verse_range.map(|r| (r.start, r.end)))).collect::<Vec<_>>();
+
+        assert_eq!(sections, vec![
+            (1, 8, Some((36, 39))), // Psalm 9:37 to the end of the chapter
+            (1, 8, Some((22, 24))), // Psalm 9:23-25
+        ]);
+    }
+}
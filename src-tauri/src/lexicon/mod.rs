@@ -0,0 +1,158 @@
+mod test;
+
+use std::{collections::HashMap, fs};
+
+use serde::{Deserialize, Serialize};
+use tauri::{path::{BaseDirectory, PathResolver}, Runtime, State};
+
+use crate::{bible::normalize_strongs, debug_release_val};
+
+// The Hebrew and Greek Strong's dictionaries, in the json format of the Open Scriptures Strong's dictionaries
+// (https://github.com/openscriptures/strongs), keyed by Strong's number:
+//   { "H7225": { "lemma": "...", "xlit": "...", "pron": "...", "derivation": "...", "strongs_def": "...", "kjv_def": "..." } }
+// The Greek dictionary uses `translit` instead of `xlit`, and has no pronunciation
+
+pub const HEBREW_LEXICON_PATH: &str = debug_release_val! {
+    debug: "resources/lexicon/small_strongs_hebrew.json",
+    release: "resources/lexicon/strongs_hebrew.json",
+};
+
+pub const GREEK_LEXICON_PATH: &str = debug_release_val! {
+    debug: "resources/lexicon/small_strongs_greek.json",
+    release: "resources/lexicon/strongs_greek.json",
+};
+
+/// The most entries returned by a search, as common glosses match a large part of the lexicon
+pub const MAX_SEARCH_RESULTS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LexiconEntry
+{
+    #[serde(default)]
+    pub number: String,
+    /// The word in the original language
+    pub lemma: String,
+    #[serde(alias = "xlit", alias = "translit")]
+    pub transliteration: String,
+    #[serde(alias = "pron", default)]
+    pub pronunciation: Option<String>,
+    #[serde(default)]
+    pub derivation: String,
+    #[serde(alias = "strongs_def", default)]
+    pub definition: String,
+    /// The English words the KJV uses to translate the word
+    #[serde(alias = "kjv_def", default)]
+    pub gloss: String,
+}
+
+pub struct LexiconDatabase
+{
+    entries: HashMap<String, LexiconEntry>,
+}
+
+impl LexiconDatabase
+{
+    /// A dictionary that can not be read is left out, rather than stopping the app from starting, so its entries are not found
+    pub fn new<R>(resolver: &PathResolver<R>) -> Self 
+        where R : Runtime
+    {
+        let mut entries = HashMap::new();
+        for path in [HEBREW_LEXICON_PATH, GREEK_LEXICON_PATH]
+        {
+            let lexicon = resolver.resolve(path, BaseDirectory::Resource)
+                .map_err(|e| e.to_string())
+                .and_then(|path| fs::read_to_string(path).map_err(|e| e.to_string()))
+                .and_then(|data| Self::parse(&[&data]));
+
+            match lexicon
+            {
+                Ok(lexicon) => entries.extend(lexicon.entries),
+                Err(err) => println!("ERROR: Failed to load the lexicon `{}`: {}", path, err),
+            }
+        }
+
+        Self { entries }
+    }
+
+    /// Parses and combines each of the json dictionaries
+    pub fn parse(sources: &[&str]) -> Result<Self, String>
+    {
+        let mut entries = HashMap::new();
+        for source in sources
+        {
+            let parsed: HashMap<String, LexiconEntry> = serde_json::from_str(source).map_err(|e| e.to_string())?;
+            for (number, mut entry) in parsed
+            {
+                let Some(number) = normalize_strongs(&number) else {
+                    return Err(format!("Invalid Strong's number `{}`", number));
+                };
+
+                entry.number = number.clone();
+                entries.insert(number, entry);
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Gets an entry by its Strong's number, such as `H7225` or `G26`
+    pub fn get_entry(&self, number: &str) -> Option<&LexiconEntry>
+    {
+        self.entries.get(&normalize_strongs(number)?)
+    }
+
+    /// Finds the entries that have every word of `gloss` in their KJV gloss or definition. Entries with the words
+    /// in their gloss come first, then each is in Strong's number order
+    pub fn search(&self, gloss: &str) -> Vec<&LexiconEntry>
+    {
+        let words = get_words(gloss);
+        if words.is_empty()
+        {
+            return vec![];
+        }
+
+        let has_words = |text: &str| {
+            let text = get_words(text);
+            words.iter().all(|w| text.contains(w))
+        };
+
+        let mut results = self.entries.values()
+            .filter_map(|e| {
+                if has_words(&e.gloss) { Some((0, e)) }
+                else if has_words(&e.definition) { Some((1, e)) }
+                else { None }
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by_key(|(rank, e)| (*rank, get_sort_key(&e.number)));
+        results.into_iter().take(MAX_SEARCH_RESULTS).map(|(_, e)| e).collect()
+    }
+}
+
+fn get_words(text: &str) -> Vec<String>
+{
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Hebrew before Greek, then by the number itself, so `H9` comes before `H10`
+fn get_sort_key(number: &str) -> (bool, u32, &str)
+{
+    let (testament, rest) = number.split_at(1);
+    let digits = rest.trim_end_matches(|c: char| !c.is_ascii_digit());
+    (testament != "H", digits.parse().unwrap_or(u32::MAX), number)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_lexicon_entry(state: State<'_, LexiconDatabase>, number: &str) -> Option<LexiconEntry>
+{
+    state.get_entry(number).cloned()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn search_lexicon(state: State<'_, LexiconDatabase>, gloss: &str) -> Vec<LexiconEntry>
+{
+    state.search(gloss).into_iter().cloned().collect()
+}
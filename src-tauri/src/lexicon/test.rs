@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests
+{
+    use crate::lexicon::LexiconDatabase;
+
+    fn load_lexicon() -> LexiconDatabase
+    {
+        LexiconDatabase::parse(&[
+            include_str!("../../resources/lexicon/small_strongs_hebrew.json"),
+            include_str!("../../resources/lexicon/small_strongs_greek.json"),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_get_entry()
+    {
+        let lexicon = load_lexicon();
+
+        let entry = lexicon.get_entry("H07225").unwrap();
+        assert_eq!(entry.number, "H7225");
+        assert_eq!(entry.transliteration, "rêʼshîyth");
+        assert_eq!(entry.pronunciation.as_deref(), Some("ray-sheeth'"));
+        assert_eq!(entry.derivation, "from the same as H7218;");
+
+        // the Greek dictionary has no pronunciations
+        let entry = lexicon.get_entry("g26").unwrap();
+        assert_eq!(entry.transliteration, "agápē");
+        assert!(entry.pronunciation.is_none());
+
+        assert!(lexicon.get_entry("H1").is_none());
+        assert!(lexicon.get_entry("love").is_none());
+    }
+
+    #[test]
+    fn test_search()
+    {
+        let lexicon = load_lexicon();
+
+        let numbers = |gloss: &str| lexicon.search(gloss).iter().map(|e| e.number.as_str()).collect::<Vec<_>>();
+        assert_eq!(numbers("love"), ["H157", "G25", "G26"]);
+        assert_eq!(numbers("Beginning"), ["H7225", "G746"]);
+
+        // entries that only have the word in their definition come after those with it in their gloss
+        assert_eq!(numbers("God"), ["H430", "G2316", "H3068"]);
+        assert_eq!(numbers("cut down"), ["H1254"]);
+        assert!(numbers("  ").is_empty());
+    }
+}
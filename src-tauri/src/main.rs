@@ -19,10 +19,17 @@ pub mod readings;
 pub mod save_data;
 pub mod cloud_sync;
 pub mod prompt;
+pub mod canon;
+pub mod comparison;
+pub mod versification;
+pub mod lexicon;
+pub mod cross_refs;
 
 use audio::{init_espeak, AudioPlayer, TtsPlayer};
 use commands::*;
 use readings::ReadingsDatabase;
+use lexicon::LexiconDatabase;
+use cross_refs::CrossReferenceDatabase;
 use tauri::{webview::PageLoadEvent, Manager, Listener};
 
 fn main() -> Result<()>
@@ -35,6 +42,8 @@ fn main() -> Result<()>
             app.manage(Mutex::new(TtsPlayer::new(app.path(), app.handle().clone())?));
             app.manage(AudioPlayer::new(app.path(), audio::DEFAULT_SOURCES));
             app.manage(ReadingsDatabase::new(app.path()));
+            app.manage(LexiconDatabase::new(app.path()));
+            app.manage(CrossReferenceDatabase::new(app.path()));
             app.manage(AppState::create(app.path(), app.handle().clone()));
 
             let app_handle_inner = app.handle().clone();
@@ -81,8 +90,11 @@ fn main() -> Result<()>
             to_previous_view_state,
             clear_view_states,
             get_chapter_text,
+            get_chapter_comparison,
+            get_chapter_diff,
             get_verse,
             get_book_name,
+            get_word_strongs,
             get_chapter_view,
             get_highlight_categories,
             add_highlight_category,
@@ -92,7 +104,19 @@ fn main() -> Result<()>
             highlight_location,
             erase_location_highlight,
             parse_bible_search,
+            get_search_history,
+            add_search_to_history,
+            save_search,
+            rerun_search,
+            set_search_pinned,
+            delete_search,
+            clear_search_history,
             run_word_search,
+            run_version_search,
+            get_word_concordance,
+            get_passage_word_frequencies,
+            search_notebook,
+            search_highlight_categories,
             add_note,
             remove_note,
             get_note,
@@ -105,6 +129,9 @@ fn main() -> Result<()>
             set_settings,
             audio::play_clip,
             readings::get_reading,
+            lexicon::get_lexicon_entry,
+            lexicon::search_lexicon,
+            cross_refs::get_cross_references,
             get_book_from_name,
             get_selected_reading,
             set_selected_reading,
@@ -113,6 +140,7 @@ fn main() -> Result<()>
             get_current_bible_version,
             set_current_bible_version,
             get_bible_versions,
+            import_bible,
             is_initialized,
             audio::run_tts_command,
             get_reader_behavior,
@@ -55,6 +55,14 @@ pub enum MigrationResult {
     Error(String),
 }
 
+/// Only saves from before notes were kept as actions need the Bibles to be migrated, so they are not loaded for any other save
+pub fn needs_bibles(data: &str) -> bool
+{
+    serde_json::from_str::<Value>(data).ok()
+        .and_then(|json| json.as_object().map(|o| !o.contains_key("note_record_saves")))
+        .unwrap_or(false)
+}
+
 pub fn migrate_save_latest(data: &str, bibles: &HashMap<String, impl AsRef<Bible>>) -> MigrationResult {
     let mut json: Value = match serde_json::from_str(data) {
         Ok(ok) => ok,
@@ -1,10 +1,10 @@
-use std::{collections::HashMap, time::SystemTime};
+use std::{collections::{HashMap, HashSet}, time::SystemTime};
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{bible::{Bible, ChapterIndex, ReferenceLocation}, notes::{HighlightCategory, NoteData, Notebook, NotebookMap, WordAnnotations}};
+use crate::{bible::{Bible, ChapterIndex, ReferenceLocation}, notes::{HighlightCategory, NoteData, Notebook, NotebookMap, WordAnnotations}, versification};
 
 pub struct NotebookActionHandler
 {
@@ -26,6 +26,19 @@ impl NotebookActionHandler
         }
     }
 
+    /// Creates a handler that only has the notebook of one Bible version, built from the actions of every notebook
+    /// (see `ActionHistory::to_version_notebook`)
+    pub fn for_version(action_history: ActionHistory, version: &str, bibles: &HashMap<String, impl AsRef<Bible>>) -> Self
+    {
+        let notebook = action_history.to_version_notebook(version, bibles);
+        Self 
+        {
+            notebook_map: NotebookMap::from([(version.to_owned(), notebook)]),
+            action_history,
+            current_action_group: vec![],
+        }
+    }
+
     pub fn get_notebooks(&self) -> &NotebookMap
     {
         &self.notebook_map
@@ -90,6 +103,16 @@ impl ActionHistory
         self.groups.sort_by(|a, b| a.time.cmp(&b.time));
     }
 
+    /// Gets the names of the Bible versions that the actions were recorded in, and of the notebooks they were performed on,
+    /// which are the versions needed to build the notebooks
+    pub fn get_bible_names(&self) -> HashSet<String>
+    {
+        self.groups.iter()
+            .flat_map(|g| &g.actions)
+            .flat_map(|a| [a.bible_name.clone(), a.notebook.clone()])
+            .collect()
+    }
+
     pub fn to_notebook_map(&self, bibles: &HashMap<String, impl AsRef<Bible>>) -> NotebookMap
     {
         let mut map = NotebookMap::new();
@@ -101,6 +124,24 @@ impl ActionHistory
         map
     }
 
+    /// Builds the notebook of a Bible version out of the actions of every notebook, so that notes and highlights made while
+    /// reading one version are shown in all of them. Each action is mapped from the version it was recorded in
+    pub fn to_version_notebook(&self, version: &str, bibles: &HashMap<String, impl AsRef<Bible>>) -> Notebook
+    {
+        let mut map = NotebookMap::new();
+        for action in self.groups.iter().flat_map(|g| &g.actions)
+        {
+            let action = Action {
+                notebook: version.to_owned(),
+                ..action.clone()
+            };
+
+            action.perform(&mut map, bibles);
+        }
+
+        map.remove(version).unwrap_or_default()
+    }
+
     pub fn merge(left: Self, right: Self) -> Self
     {
         let mut groups = Vec::new();
@@ -185,11 +226,28 @@ pub struct Action
 
 impl Action
 {
+    /// `bible_name` is the version that the locations of the action were taken from. A notebook named after a Bible version
+    /// holds locations in that version, so if the action was recorded in a different version, its locations are mapped into the
+    /// notebook's version first
     pub fn perform(&self, notebooks: &mut NotebookMap, bibles: &HashMap<String, impl AsRef<Bible>>)
     {
         let notebook = notebooks.entry(self.notebook.clone()).or_default();
-        let bible = bibles.get(&self.bible_name).unwrap().as_ref();
-        self.action.perform(notebook, bible);
+        let from = bibles.get(&self.bible_name).map(|b| b.as_ref());
+        let to = bibles.get(&self.notebook).map(|b| b.as_ref());
+
+        match (from, to)
+        {
+            (from, Some(to)) if self.notebook != self.bible_name => {
+                // if the version it was recorded in is not installed, the locations are kept as they are, if they exist in `to`
+                if let Some(action) = self.action.map_locations(from.unwrap_or(to), to)
+                {
+                    action.perform(notebook, to);
+                }
+            },
+            (Some(bible), _) => self.action.perform(notebook, bible),
+            // only possible for notebooks that are not named after a version. The action is still kept in the history
+            _ => println!("Could not perform action for notebook `{}`, as Bible `{}` is not installed", self.notebook, self.bible_name),
+        }
     }
 }
 
@@ -281,6 +339,38 @@ impl ActionType
         }
     }
 
+    /// Maps any locations in the action from the `from` Bible to the `to` Bible. Locations that do not exist in `to` are
+    /// dropped, and returns `None` if the action has no locations left to act on
+    pub fn map_locations(&self, from: &Bible, to: &Bible) -> Option<ActionType>
+    {
+        // a note that had locations, but has none that exist in `to`, is left out rather than being moved nowhere
+        let map_all = |locations: &Vec<ReferenceLocation>| {
+            let mapped = locations.iter().filter_map(|l| versification::map_location(l, from, to)).collect_vec();
+            (!mapped.is_empty() || locations.is_empty()).then_some(mapped)
+        };
+
+        let action = match self
+        {
+            ActionType::CreateNote(note_data) => ActionType::CreateNote(NoteData { locations: map_all(&note_data.locations)?, ..note_data.clone() }),
+            ActionType::EditNote(note_data) => ActionType::EditNote(NoteData { locations: map_all(&note_data.locations)?, ..note_data.clone() }),
+            ActionType::Highlight { highlight_id, location } => ActionType::Highlight { 
+                highlight_id: highlight_id.clone(), 
+                location: versification::map_location(location, from, to)?,
+            },
+            ActionType::Erase { highlight_id, location } => ActionType::Erase { 
+                highlight_id: highlight_id.clone(), 
+                location: versification::map_location(location, from, to)?,
+            },
+            ActionType::EditNoteLocations { note_id, locations } => ActionType::EditNoteLocations { 
+                note_id: note_id.clone(), 
+                locations: map_all(locations)?,
+            },
+            _ => self.clone(),
+        };
+
+        Some(action)
+    }
+
     fn highlight_word(notebook: &mut Notebook, chapter: &ChapterIndex, word_position: u32, highlight_id: &str)
     {
         let chapter_annotations = match notebook.annotations.get_mut(&chapter) {
@@ -1,4 +1,5 @@
 pub mod action;
+pub mod search;
 mod test;
 
 use std::collections::HashMap;
@@ -0,0 +1,172 @@
+use itertools::Itertools;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{bible::ReferenceLocation, searching::SectionSearchResult};
+
+use super::{HighlightCategory, NoteData, NoteSourceType, Notebook};
+
+lazy_static::lazy_static! {
+    static ref HTML_TAG_REGEX: Regex = Regex::new(r"<[^>]*>").unwrap();
+    static ref MARKDOWN_LINK_REGEX: Regex = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+    static ref MARKDOWN_SYNTAX_REGEX: Regex = Regex::new(r"(?m)^\s*(?:#{1,6}|>|[-*+]|\d+\.)\s+|[*_~`]+").unwrap();
+}
+
+/// What to search the notes of a notebook by. A note must match everything that is given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotebookQuery
+{
+    /// Words that must all be in the text of the note, ignoring case and formatting
+    #[serde(default)]
+    pub text: Option<String>,
+    /// The id or name of a highlight category, that some of the note's words must be highlighted with
+    #[serde(default)]
+    pub category: Option<String>,
+    /// A passage that one of the note's locations must overlap with
+    #[serde(default)]
+    pub passage: Option<SectionSearchResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteSearchResult
+{
+    pub id: String,
+    pub locations: Vec<ReferenceLocation>,
+    /// The ids of the highlight categories used on the note's words
+    pub categories: Vec<String>,
+}
+
+impl Notebook
+{
+    /// Finds the notes matching the query, sorted by their first location
+    pub fn search_notes(&self, query: &NotebookQuery) -> Vec<NoteSearchResult>
+    {
+        let words = query.text.as_deref().map_or(vec![], get_search_words);
+        let category = query.category.as_deref().and_then(|c| self.find_highlight_category(c));
+        if query.category.is_some() && category.is_none()
+        {
+            return vec![];
+        }
+
+        self.notes.values()
+            .filter(|note| words.is_empty() || {
+                let text = get_plain_text(&note.text, note.source_type).to_lowercase();
+                words.iter().all(|w| text.contains(w.as_str()))
+            })
+            .filter(|note| query.passage.as_ref().is_none_or(|p| note.locations.iter().any(|l| overlaps_passage(l, p))))
+            .map(|note| NoteSearchResult {
+                id: note.id.clone(),
+                locations: note.locations.clone(),
+                categories: self.get_note_categories(note),
+            })
+            .filter(|result| category.is_none_or(|c| result.categories.contains(&c.id)))
+            .sorted_by_key(|result| {
+                let first = result.locations.iter().map(|l| (l.chapter.book, l.chapter.number, l.range.verse_start, l.range.word_start)).min();
+                (first.is_none(), first, result.id.clone())
+            })
+            .collect()
+    }
+
+    /// Finds the highlight categories with all the words of `text` in their name or description, sorted by priority
+    pub fn search_highlight_categories(&self, text: &str) -> Vec<&HighlightCategory>
+    {
+        let words = get_search_words(text);
+        self.highlight_categories.values()
+            .filter(|category| {
+                let text = format!("{} {}", category.name, get_plain_text(&category.description, category.source_type)).to_lowercase();
+                words.iter().all(|w| text.contains(w.as_str()))
+            })
+            .sorted_by_key(|category| (category.priority, category.name.to_lowercase()))
+            .collect()
+    }
+
+    /// Gets a category by its id, or by its name (ignoring case)
+    fn find_highlight_category(&self, category: &str) -> Option<&HighlightCategory>
+    {
+        self.highlight_categories.get(category)
+            .or_else(|| self.highlight_categories.values().find(|c| c.name.trim().eq_ignore_ascii_case(category.trim())))
+    }
+
+    fn get_note_categories(&self, note: &NoteData) -> Vec<String>
+    {
+        // every annotated word of a note lists it, so its highlights can be found without the Bible
+        note.locations.iter()
+            .map(|l| l.chapter)
+            .unique()
+            .filter_map(|chapter| self.annotations.get(&chapter))
+            .flat_map(|words| words.values())
+            .filter(|word| word.notes.contains(&note.id))
+            .flat_map(|word| word.highlights.iter().cloned())
+            .unique()
+            .sorted()
+            .collect()
+    }
+}
+
+/// Gets the text of a note (or a highlight category's description) without any of its formatting
+pub fn get_plain_text(text: &str, source_type: NoteSourceType) -> String
+{
+    let text = match source_type
+    {
+        NoteSourceType::Html => HTML_TAG_REGEX.replace_all(text, " ").into_owned(),
+        NoteSourceType::Markdown => {
+            let text = MARKDOWN_LINK_REGEX.replace_all(text, "$1");
+            let text = MARKDOWN_SYNTAX_REGEX.replace_all(&text, "");
+            HTML_TAG_REGEX.replace_all(&text, " ").into_owned()
+        },
+        NoteSourceType::Json => {
+            // an editor document, with the text in the `text` of its leaf nodes
+            let mut plain_text = String::new();
+            if let Ok(document) = serde_json::from_str::<Value>(text)
+            {
+                push_json_text(&document, &mut plain_text);
+            }
+
+            plain_text
+        },
+    };
+
+    decode_html_entities(&text).split_whitespace().join(" ")
+}
+
+fn push_json_text(node: &Value, text: &mut String)
+{
+    if let Some(node_text) = node.get("text").and_then(|t| t.as_str())
+    {
+        text.push_str(node_text);
+    }
+
+    if let Some(content) = node.get("content").and_then(|c| c.as_array())
+    {
+        content.iter().for_each(|child| push_json_text(child, text));
+
+        // blocks, such as paragraphs, are separate from each other
+        text.push(' ');
+    }
+}
+
+fn decode_html_entities(text: &str) -> String
+{
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn get_search_words(text: &str) -> Vec<String>
+{
+    text.split_whitespace().map(|w| w.to_lowercase()).collect()
+}
+
+fn overlaps_passage(location: &ReferenceLocation, passage: &SectionSearchResult) -> bool
+{
+    if location.chapter.book != passage.book || location.chapter.number != passage.chapter
+    {
+        return false;
+    }
+
+    passage.verse_range.is_none_or(|r| location.range.verse_start <= r.end && r.start <= location.range.verse_end)
+}
@@ -4,30 +4,35 @@ mod tests {
     use std::sync::Arc;
     use std::time::{SystemTime, Duration};
     use uuid::Uuid;
-    use crate::bible::{Bible, Book, Chapter, Verse, Word, ChapterIndex, ReferenceLocation, WordRange};
+    use crate::bible::{Bible, Book, Chapter, Verse, Word, ChapterIndex, ReferenceLocation, WordRange, Testament};
     use crate::notes::action::{Action, ActionGroup, ActionHistory, ActionType, NotebookActionHandler};
-    use crate::notes::{HighlightCategory, NoteData, NoteSourceType};
+    use crate::notes::{HighlightCategory, NoteData, NoteSourceType, Notebook};
+    use crate::notes::search::{get_plain_text, NotebookQuery};
+    use crate::searching::SectionSearchResult;
     use crate::utils::Color;
+    use crate::versification::Versification;
 
     // Test data builders
     fn create_test_bible() -> Bible {
         let words = vec![
-            Word { text: "In".to_string(), italicized: false, red: false },
-            Word { text: "the".to_string(), italicized: false, red: false },
-            Word { text: "beginning".to_string(), italicized: false, red: false },
-            Word { text: "God".to_string(), italicized: false, red: true },
-            Word { text: "created".to_string(), italicized: false, red: false },
-            Word { text: "the".to_string(), italicized: false, red: false },
-            Word { text: "heavens".to_string(), italicized: false, red: false },
-            Word { text: "and".to_string(), italicized: false, red: false },
-            Word { text: "the".to_string(), italicized: false, red: false },
-            Word { text: "earth".to_string(), italicized: false, red: false },
+            Word { text: "In".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "the".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "beginning".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "God".to_string(), italicized: false, red: true, strongs: vec![], morph: None },
+            Word { text: "created".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "the".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "heavens".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "and".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "the".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
+            Word { text: "earth".to_string(), italicized: false, red: false, strongs: vec![], morph: None },
         ];
 
         let verse = Verse { words };
         let chapter = Chapter { verses: vec![verse] };
         let book = Book {
             name: "Genesis".to_string(),
+            id: "GEN".to_string(),
+            testament: Testament::Old,
             chapters: vec![chapter],
         };
 
@@ -35,6 +40,7 @@ mod tests {
             name: "Test Bible".to_string(),
             desc: "A simple test bible".to_string(),
             books: vec![book],
+            versification: Versification::Kjv,
         }
     }
 
@@ -503,4 +509,124 @@ mod tests {
         
         println!("✓ NotebookActionHandler test passed");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_note_plain_text() {
+        assert_eq!(get_plain_text("# Grace\n\nBy **grace** through [faith](https://example.com) &amp; `works`", NoteSourceType::Markdown), "Grace By grace through faith & works");
+        assert_eq!(get_plain_text("<p>In the <em>beginning</em></p><p>God</p>", NoteSourceType::Html), "In the beginning God");
+
+        let json = r#"{"type":"doc","content":[{"type":"paragraph","content":[{"type":"text","text":"Light "},{"type":"text","marks":[{"type":"bold"}],"text":"shines"}]},{"type":"paragraph","content":[{"type":"text","text":"again"}]}]}"#;
+        assert_eq!(get_plain_text(json, NoteSourceType::Json), "Light shines again");
+        assert_eq!(get_plain_text("", NoteSourceType::Json), "");
+    }
+
+    #[test]
+    fn test_search_notes() {
+        let bible = create_test_bible();
+        let mut notebook = Notebook::default();
+
+        let category = create_highlight_category("creation", "Creation", Color { r: 255, g: 0, b: 0 }, 1);
+        notebook.highlight_categories.insert(category.id.clone(), category);
+
+        notebook.add_note(create_note("first", "About *the* **heavens**", 5, 6), &bible);
+        notebook.add_note(create_note("second", "<p>The earth</p>", 8, 9), &bible);
+
+        // highlight the words of the first note
+        for word in notebook.annotations.get_mut(&ChapterIndex { book: 0, number: 0 }).unwrap().values_mut() {
+            if word.notes.contains(&"first".to_string()) {
+                word.highlights.push("creation".to_string());
+            }
+        }
+
+        let search = |query: NotebookQuery| notebook.search_notes(&query).into_iter().map(|r| r.id).collect::<Vec<_>>();
+
+        assert_eq!(search(NotebookQuery::default()), vec!["first", "second"]);
+        assert_eq!(search(NotebookQuery { text: Some("THE heavens".into()), ..Default::default() }), vec!["first"]);
+        assert_eq!(search(NotebookQuery { text: Some("earth".into()), ..Default::default() }), vec!["second"]);
+        assert_eq!(search(NotebookQuery { category: Some("creation".into()), ..Default::default() }), vec!["first"]);
+        assert_eq!(search(NotebookQuery { category: Some("CREATION".into()), ..Default::default() }), vec!["first"]);
+        assert!(search(NotebookQuery { category: Some("missing".into()), ..Default::default() }).is_empty());
+
+        let passage = |book, chapter| Some(SectionSearchResult { book, chapter, verse_range: None });
+        assert_eq!(search(NotebookQuery { text: Some("the".into()), passage: passage(0, 0), ..Default::default() }), vec!["first", "second"]);
+        assert!(search(NotebookQuery { passage: passage(0, 1), ..Default::default() }).is_empty());
+
+        let categories = notebook.search_highlight_categories("creation passages");
+        assert_eq!(categories.len(), 1);
+        assert!(notebook.search_highlight_categories("judgement").is_empty());
+    }
+
+    fn create_psalms_bible(name: &str, versification: Versification, chapter_count: usize) -> Bible {
+        let verse = || Verse {
+            words: ["Praise", "the", "LORD"].iter().map(|w| Word { text: w.to_string(), italicized: false, red: false, strongs: vec![], morph: None }).collect(),
+        };
+
+        let chapters = (0..chapter_count).map(|_| Chapter { verses: (0..3).map(|_| verse()).collect() }).collect();
+        Bible {
+            name: name.to_string(),
+            desc: String::new(),
+            books: vec![Book { name: "Psalms".to_string(), id: "PSA".to_string(), testament: Testament::Old, chapters }],
+            versification,
+        }
+    }
+
+    #[test]
+    fn test_action_versification_mapping() {
+        let bibles = HashMap::from([
+            ("KJV".to_string(), Arc::new(create_psalms_bible("KJV", Versification::Kjv, 11))),
+            ("VULG".to_string(), Arc::new(create_psalms_bible("VULG", Versification::Vulgate, 10))),
+        ]);
+
+        // Psalm 11:2 in the KJV is Psalm 10:2 in the Vulgate
+        let kjv_location = ReferenceLocation {
+            chapter: ChapterIndex { book: 0, number: 10 },
+            range: WordRange { verse_start: 1, word_start: 1, verse_end: 1, word_end: 2 },
+        };
+        let vulgate_location = ReferenceLocation { chapter: ChapterIndex { book: 0, number: 9 }, ..kjv_location };
+
+        let note = NoteData { locations: vec![kjv_location], ..create_note("psalm", "The LORD", 0, 0) };
+        let action = Action {
+            notebook: "VULG".to_string(),
+            bible_name: "KJV".to_string(),
+            action: ActionType::CreateNote(note.clone()),
+        };
+
+        let mut notebooks = HashMap::new();
+        action.perform(&mut notebooks, &bibles);
+        assert_eq!(notebooks["VULG"].notes["psalm"].locations, vec![vulgate_location]);
+
+        // an action recorded in a version that is no longer installed is still performed, without mapping its locations
+        let action = Action {
+            bible_name: "NIV".to_string(),
+            action: ActionType::CreateNote(NoteData { locations: vec![vulgate_location], ..note.clone() }),
+            ..action
+        };
+
+        let mut notebooks = HashMap::new();
+        action.perform(&mut notebooks, &bibles);
+        assert_eq!(notebooks["VULG"].notes["psalm"].locations, vec![vulgate_location]);
+
+        // the notebook of each version has the notes made in every version
+        let mut history = ActionHistory::new();
+        history.push(ActionGroup {
+            id: Uuid::new_v4(),
+            actions: vec![Action { notebook: "KJV".to_string(), bible_name: "KJV".to_string(), action: ActionType::CreateNote(note) }],
+            time: SystemTime::now(),
+        });
+
+        assert_eq!(history.to_version_notebook("KJV", &bibles).notes["psalm"].locations, vec![kjv_location]);
+        assert_eq!(history.to_version_notebook("VULG", &bibles).notes["psalm"].locations, vec![vulgate_location]);
+
+        // Psalm 10:2 in the KJV is Psalm 9:22 in the Vulgate, which this Vulgate does not have, so the note can not be mapped
+        let missing = ReferenceLocation { chapter: ChapterIndex { book: 0, number: 9 }, ..kjv_location };
+        let unmappable = ActionType::CreateNote(NoteData { locations: vec![missing], ..create_note("missing", "The LORD", 0, 0) });
+        assert!(unmappable.map_locations(&bibles["KJV"], &bibles["VULG"]).is_none());
+
+        let mut notebooks = HashMap::new();
+        Action { notebook: "VULG".to_string(), bible_name: "KJV".to_string(), action: unmappable }.perform(&mut notebooks, &bibles);
+        assert!(notebooks["VULG"].notes.is_empty());
+
+                let handler = NotebookActionHandler::for_version(history, "VULG", &bibles);
+        assert_eq!(handler.get_notebooks().keys().collect::<Vec<_>>(), vec!["VULG"]);
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use uuid::Uuid;
 
-use crate::{app_state::{ViewState, DEFAULT_BIBLE}, audio::{reader_behavior::ReaderBehavior, TtsSettings}, bible::{Bible, ChapterIndex}, cloud_sync::sync_state::CloudSyncStateSave, migration::{self, MigrationResult}, notes::action::ActionHistory, settings::Settings};
+use crate::{app_state::{ViewState, DEFAULT_BIBLE}, audio::{reader_behavior::ReaderBehavior, TtsSettings}, bible::{Bible, ChapterIndex}, cloud_sync::sync_state::CloudSyncStateSave, migration::{self, MigrationResult}, notes::action::ActionHistory, searching::history::SearchHistory, settings::Settings};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AppSaveVersion
@@ -86,7 +86,10 @@ pub struct LocalDeviceSave
     pub tts_settings: TtsSettings,
     pub reader_behavior: ReaderBehavior,
     pub recent_highlights: Vec<Uuid>,
-    pub cloud_sync_save: CloudSyncStateSave
+    pub cloud_sync_save: CloudSyncStateSave,
+    // saves from before searches were remembered have no history
+    #[serde(default)]
+    pub search_history: SearchHistory,
 }
 
 impl Default for LocalDeviceSave
@@ -112,6 +115,7 @@ impl Default for LocalDeviceSave
             reader_behavior: ReaderBehavior::default(),
             recent_highlights: vec![],
             cloud_sync_save: CloudSyncStateSave::default(),
+            search_history: SearchHistory::default(),
         }
     }
 }
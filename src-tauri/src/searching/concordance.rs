@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::bible::Bible;
+
+use super::{index::{normalize_word, SearchIndex}, query::SearchTerm, SectionSearchResult};
+
+/// Words that make up most of any English passage, and are left out of word frequencies when asked
+const COMMON_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "he", "her", "him", "his", "i", "in", "is",
+    "it", "me", "my", "not", "of", "on", "or", "that", "the", "thee", "their", "them", "they", "thou", "thy", "to",
+    "unto", "was", "we", "were", "which", "with", "ye", "you", "your",
+];
+
+/// Where a word or phrase is used in a Bible, with the number of times it is used in each book and chapter.
+/// Books and chapters without any uses are left out. All indices are 0 based
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Concordance
+{
+    /// The number of times the word or phrase is used
+    pub total: u32,
+    /// The number of verses it is used in
+    pub verse_count: u32,
+    pub books: Vec<BookOccurrences>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookOccurrences
+{
+    pub book: u32,
+    pub count: u32,
+    pub chapters: Vec<ChapterOccurrences>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChapterOccurrences
+{
+    pub chapter: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordFrequency
+{
+    pub word: String,
+    pub count: u32,
+}
+
+/// Counts the uses of a word or phrase. Quotes around the text are optional, and each word can be a pattern, as used by `SearchIndex::find_word`
+pub fn get_concordance(text: &str, index: &SearchIndex) -> Result<Concordance, String>
+{
+    let term = SearchTerm::parse(&format!("\"{}\"", text.trim().trim_matches('"')));
+    term.check_words()?;
+
+    let postings = index.find_phrase(term.words());
+    let mut concordance = Concordance {
+        total: postings.len() as u32,
+        verse_count: postings.iter().map(|p| p.verse).dedup().count() as u32,
+        books: vec![],
+    };
+
+    // postings are in canonical order, so each book and chapter is only seen once
+    for posting in postings
+    {
+        let Some(verse) = index.get_verse(posting.verse) else { continue; };
+
+        if concordance.books.last().is_none_or(|b| b.book != verse.book)
+        {
+            concordance.books.push(BookOccurrences { book: verse.book, count: 0, chapters: vec![] });
+        }
+
+        let book = concordance.books.last_mut().unwrap();
+        book.count += 1;
+
+        match book.chapters.last_mut()
+        {
+            Some(chapter) if chapter.chapter == verse.chapter => chapter.count += 1,
+            _ => book.chapters.push(ChapterOccurrences { chapter: verse.chapter, count: 1 }),
+        }
+    }
+
+    Ok(concordance)
+}
+
+/// Gets the `count` most used words in a passage, from most to least used. Words used the same number of times are sorted alphabetically.
+/// If `ignore_common`, the most common English words (such as `the` and `and`) are left out
+pub fn get_word_frequencies(bible: &Bible, passage: &SectionSearchResult, count: usize, ignore_common: bool) -> Vec<WordFrequency>
+{
+    let Some(chapter) = bible.books.get(passage.book as usize).and_then(|b| b.chapters.get(passage.chapter as usize)) else {
+        return vec![];
+    };
+
+    let verses = match passage.verse_range
+    {
+        Some(range) => chapter.verses.get(range.start as usize..=(range.end as usize).min(chapter.verses.len().saturating_sub(1))).unwrap_or_default(),
+        None => &chapter.verses,
+    };
+
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for word in verses.iter().flat_map(|v| &v.words)
+    {
+        let word = normalize_word(&word.text);
+        if word.is_empty() || (ignore_common && COMMON_WORDS.contains(&word.as_str())) { continue; }
+
+        *counts.entry(word).or_default() += 1;
+    }
+
+    counts.into_iter()
+        .map(|(word, count)| WordFrequency { word, count })
+        .sorted_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)))
+        .take(count)
+        .collect()
+}
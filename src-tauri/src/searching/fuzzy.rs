@@ -0,0 +1,72 @@
+use itertools::Itertools;
+
+use crate::{bible::Bible, canon};
+
+// Finds the books that were most likely meant by a misspelled name, such as `Deutronomy` or `Phillipians`. The name is compared
+// with every name and abbreviation of each book (see `canon`), and the name of the book in the Bible, as normalized by `canon`,
+// counting the number of letters that would have to be added, removed, replaced or swapped with their neighbor to make it match
+
+/// The most books that are suggested
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Gets the names (in the Bible) of the books closest to a misspelled name, closest first. Books are only suggested if about one
+/// in four of the letters are wrong, or one letter for short names. Books that are equally close are in the order of the Bible
+pub fn suggest_books(prefix: Option<u32>, book_name: &str, bible: &Bible) -> Vec<String>
+{
+    let name = match prefix
+    {
+        Some(prefix) => canon::normalize_name(&format!("{} {}", prefix, book_name)),
+        None => canon::normalize_name(book_name),
+    };
+
+    let max_distance = (name.chars().count() / 4).max(1);
+
+    bible.books.iter().enumerate()
+        .filter_map(|(i, book)| {
+            let canon_names = canon::get_book(&book.id).into_iter()
+                .flat_map(|b| [b.name, b.osis_id].into_iter().chain(b.alt_names.iter().copied()));
+
+            let distance = canon_names.chain([book.name.as_str()])
+                .map(|n| edit_distance(&name, &canon::normalize_name(n)))
+                .min()?;
+
+            (distance <= max_distance).then_some((distance, i, book.name.clone()))
+        })
+        .sorted()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, name)| name)
+        .collect()
+}
+
+/// The number of characters that have to be added, removed, replaced, or swapped with the character next to them,
+/// to turn one string into the other
+pub fn edit_distance(a: &str, b: &str) -> usize
+{
+    let a = a.chars().collect_vec();
+    let b = b.chars().collect_vec();
+
+    // the distances between the starts of the strings, for the last two rows as well as the current one
+    let mut before_last = vec![0; b.len() + 1];
+    let mut last = (0..=b.len()).collect_vec();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len()
+    {
+        current[0] = i;
+        for j in 1..=b.len()
+        {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current[j] = (last[j] + 1).min(current[j - 1] + 1).min(last[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1]
+            {
+                current[j] = current[j].min(before_last[j - 2] + 1);
+            }
+        }
+
+        std::mem::swap(&mut before_last, &mut last);
+        std::mem::swap(&mut last, &mut current);
+    }
+
+    last[b.len()]
+}
@@ -0,0 +1,134 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// The searches made from the search bar, kept in the local save so they outlast the view states. A search is kept as the text
+// that was typed, and is parsed again when it is rerun, so it always searches the current version.
+//
+// Recent searches are forgotten once there are more than `MAX_RECENT_SEARCHES`, unless they are pinned. Saved searches have a
+// name, and are only removed when deleted
+
+/// The most unpinned, unnamed searches that are remembered
+pub const MAX_RECENT_SEARCHES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch
+{
+    pub id: String,
+    pub text: String,
+    /// Only given for searches saved by the user. Searches without a name are the search history
+    pub name: Option<String>,
+    pub pinned: bool,
+    /// When the search was last run, in seconds since the Unix epoch
+    pub last_run: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHistory
+{
+    /// Newest first
+    searches: Vec<SavedSearch>,
+}
+
+impl SearchHistory
+{
+    /// Gets every search, with pinned searches first, then saved searches, then the rest of the history, each newest first
+    pub fn get_searches(&self) -> Vec<SavedSearch>
+    {
+        self.searches.iter()
+            .sorted_by_key(|s| (!s.pinned, s.name.is_none()))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a search to the history. A search that is already in the history is moved to the front, instead of being repeated
+    pub fn add_recent(&mut self, text: &str)
+    {
+        let text = text.trim();
+        if text.is_empty() { return; }
+
+        let search = match self.searches.iter().position(|s| s.name.is_none() && s.text == text)
+        {
+            Some(i) => self.searches.remove(i),
+            None => SavedSearch {
+                id: Uuid::new_v4().to_string(),
+                text: text.to_owned(),
+                name: None,
+                pinned: false,
+                last_run: 0,
+            },
+        };
+
+        self.searches.insert(0, search);
+        self.searches[0].last_run = now();
+
+        // forget the oldest searches that are not kept for another reason
+        let mut recent_count = 0;
+        self.searches.retain(|s| {
+            if s.pinned || s.name.is_some() { return true; }
+            recent_count += 1;
+            recent_count <= MAX_RECENT_SEARCHES
+        });
+    }
+
+    /// Saves a search with a name, returning its id
+    pub fn save(&mut self, text: &str, name: &str) -> String
+    {
+        let search = SavedSearch {
+            id: Uuid::new_v4().to_string(),
+            text: text.trim().to_owned(),
+            name: Some(name.trim().to_owned()),
+            pinned: false,
+            last_run: now(),
+        };
+
+        let id = search.id.clone();
+        self.searches.insert(0, search);
+        id
+    }
+
+    /// Marks a search as run now, moving it to the front. Returns its text, or None if there is no such search
+    pub fn rerun(&mut self, id: &str) -> Option<String>
+    {
+        let i = self.searches.iter().position(|s| s.id == id)?;
+        let search = self.searches.remove(i);
+        self.searches.insert(0, search);
+        self.searches[0].last_run = now();
+
+        Some(self.searches[0].text.clone())
+    }
+
+    /// Returns false if there is no such search
+    pub fn set_pinned(&mut self, id: &str, pinned: bool) -> bool
+    {
+        match self.searches.iter_mut().find(|s| s.id == id)
+        {
+            Some(search) => {
+                search.pinned = pinned;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns false if there is no such search
+    pub fn remove(&mut self, id: &str) -> bool
+    {
+        let count = self.searches.len();
+        self.searches.retain(|s| s.id != id);
+        self.searches.len() != count
+    }
+
+    /// Removes every search that is not pinned or saved
+    pub fn clear_recent(&mut self)
+    {
+        self.searches.retain(|s| s.pinned || s.name.is_some());
+    }
+}
+
+fn now() -> u64
+{
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
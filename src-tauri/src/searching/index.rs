@@ -0,0 +1,259 @@
+use std::{borrow::Cow, collections::{BTreeMap, HashMap}, ops::Range};
+
+use crate::bible::{Bible, Testament};
+
+use super::stemmer::stem_word;
+
+// An inverted index of a Bible, mapping every word (normalized with `normalize_word`) to each place it is used.
+// Verses are numbered in the order they appear in the Bible, so the postings of a word are in canonical order,
+// and sorted by word within each verse. Words are kept in a sorted map, so that words sharing a prefix are next to each other.
+//
+// Words can be looked up with a pattern (see `find_word`):
+//   `believe`    the word itself
+//   `believ*`    any word matching the pattern, where `*` stands for any number of letters
+//   `~believe`   any form of the word, with the same stem (such as `believed`, `believeth` or `believing`)
+
+/// A verse of the indexed Bible. All indices are 0 based
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndexedVerse
+{
+    pub book: u32,
+    pub chapter: u32,
+    pub verse: u32,
+    /// The number of words in the verse, not counting any that are only punctuation
+    pub word_count: u32,
+}
+
+/// A book of the indexed Bible, with the positions of its verses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedBook
+{
+    pub id: String,
+    pub testament: Testament,
+    pub verses: Range<u32>,
+}
+
+/// A use of a word. `verse` is the position of the verse in the whole Bible, and `word` is the index of the word in the verse.
+/// `position` counts only the indexed words of the verse, so words are next to each other even with punctuation between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WordPosting
+{
+    pub verse: u32,
+    pub word: u32,
+    pub position: u32,
+}
+
+/// A use of a phrase in a verse, from the index of its first word in the verse to the index of its last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PhraseUse
+{
+    pub verse: u32,
+    pub word_start: u32,
+    pub word_end: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchIndex
+{
+    books: Vec<IndexedBook>,
+    verses: Vec<IndexedVerse>,
+    words: BTreeMap<String, Vec<WordPosting>>,
+    stems: HashMap<String, Vec<String>>,
+}
+
+impl SearchIndex
+{
+    pub fn new(bible: &Bible) -> Self
+    {
+        let mut index = Self::default();
+        for (book_index, book) in bible.books.iter().enumerate()
+        {
+            let first_verse = index.verses.len() as u32;
+            for (chapter_index, chapter) in book.chapters.iter().enumerate()
+            {
+                for (verse_index, verse) in chapter.verses.iter().enumerate()
+                {
+                    let verse_number = index.verses.len() as u32;
+                    let mut word_count = 0;
+
+                    for (word_index, word) in verse.words.iter().enumerate()
+                    {
+                        let word = normalize_word(&word.text);
+                        if word.is_empty() { continue; }
+
+                        index.words.entry(word).or_default().push(WordPosting {
+                            verse: verse_number,
+                            word: word_index as u32,
+                            position: word_count,
+                        });
+                        word_count += 1;
+                    }
+
+                    index.verses.push(IndexedVerse {
+                        book: book_index as u32,
+                        chapter: chapter_index as u32,
+                        verse: verse_index as u32,
+                        word_count,
+                    });
+                }
+            }
+
+            index.books.push(IndexedBook {
+                id: book.id.clone(),
+                testament: book.testament,
+                verses: first_verse..index.verses.len() as u32,
+            });
+        }
+
+        for word in index.words.keys()
+        {
+            index.stems.entry(stem_word(word)).or_default().push(word.clone());
+        }
+
+        index
+    }
+
+    /// Gets every use of a word, ignoring case and any surrounding punctuation
+    pub fn get_postings(&self, word: &str) -> &[WordPosting]
+    {
+        self.words.get(&normalize_word(word)).map_or(&[], |p| p.as_slice())
+    }
+
+    /// Gets every use of the words matching a pattern (see above), in order. Patterns are normalized with `normalize_pattern`
+    pub fn find_word(&self, pattern: &str) -> Cow<'_, [WordPosting]>
+    {
+        let pattern = normalize_pattern(pattern);
+        if let Some(word) = pattern.strip_prefix('~')
+        {
+            let words = self.stems.get(&stem_word(word)).map_or(&[][..], |w| w.as_slice());
+            return merge_postings(words.iter().map(|w| self.words[w].as_slice()));
+        }
+
+        let Some((prefix, _)) = pattern.split_once('*') else {
+            return Cow::Borrowed(self.get_postings(&pattern));
+        };
+
+        let words = self.words.range(prefix.to_owned()..)
+            .take_while(|(word, _)| word.starts_with(prefix))
+            .filter(|(word, _)| matches_wildcard(word, &pattern))
+            .map(|(_, postings)| postings.as_slice());
+
+        merge_postings(words)
+    }
+
+    /// Finds every use of the words one after another, in order, within a verse. Each word can be a pattern, as used by `find_word`.
+    /// Words only separated by punctuation are still one after another
+    pub fn find_phrase<S>(&self, words: &[S]) -> Vec<PhraseUse>
+        where S : AsRef<str>
+    {
+        let Some((first, rest)) = words.split_first() else {
+            return vec![];
+        };
+
+        // postings are sorted by word, so they are sorted by position too
+        let rest = rest.iter().map(|w| self.find_word(w.as_ref())).collect::<Vec<_>>();
+        self.find_word(first.as_ref()).iter().filter_map(|start| {
+            let mut word_end = start.word;
+            for (postings, offset) in rest.iter().zip(1..)
+            {
+                let i = postings.binary_search_by_key(&(start.verse, start.position + offset), |p| (p.verse, p.position)).ok()?;
+                word_end = postings[i].word;
+            }
+
+            Some(PhraseUse { verse: start.verse, word_start: start.word, word_end })
+        }).collect()
+    }
+
+    /// Gets a verse from its position in the Bible, as used by `WordPosting::verse`
+    pub fn get_verse(&self, verse: u32) -> Option<IndexedVerse>
+    {
+        self.verses.get(verse as usize).copied()
+    }
+
+    pub fn books(&self) -> &[IndexedBook]
+    {
+        &self.books
+    }
+
+    pub fn verse_count(&self) -> u32
+    {
+        self.verses.len() as u32
+    }
+
+    /// The number of distinct words in the Bible
+    pub fn word_count(&self) -> usize
+    {
+        self.words.len()
+    }
+}
+
+/// Lowercases a word, and removes any punctuation (or whitespace) from either end. Punctuation within the word,
+/// such as the apostrophe in `Lord's`, is kept
+pub fn normalize_word(word: &str) -> String
+{
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Like `normalize_word`, but keeps the `~` and `*`s of a pattern
+pub fn normalize_pattern(pattern: &str) -> String
+{
+    let (stem, word) = pattern.strip_prefix('~').map_or(("", pattern), |w| ("~", w));
+    format!("{}{}", stem, word.trim_matches(|c: char| !(c.is_alphanumeric() || c == '*')).to_lowercase())
+}
+
+/// If a word matches a pattern, where each `*` can stand for any number of characters
+fn matches_wildcard(word: &str, pattern: &str) -> bool
+{
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = word.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty(); // no `*` at all
+    };
+
+    for part in middle
+    {
+        match rest.find(part)
+        {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Merges the postings of several words into one sorted list
+fn merge_postings<'a>(postings: impl Iterator<Item = &'a [WordPosting]>) -> Cow<'a, [WordPosting]>
+{
+    let mut postings = postings.collect::<Vec<_>>();
+    if postings.len() == 1
+    {
+        return Cow::Borrowed(postings.remove(0));
+    }
+
+    let mut merged = postings.concat();
+    merged.sort_unstable();
+    Cow::Owned(merged)
+}
+
+/// Groups the uses of a phrase by their verse, giving the first and last word of each use in the verse. Both are in the same order as the uses
+pub fn group_verses(uses: &[PhraseUse]) -> Vec<(u32, Vec<(u32, u32)>)>
+{
+    let mut verses: Vec<(u32, Vec<(u32, u32)>)> = vec![];
+    for phrase in uses
+    {
+        let words = (phrase.word_start, phrase.word_end);
+        match verses.last_mut()
+        {
+            Some((verse, uses)) if *verse == phrase.verse => uses.push(words),
+            _ => verses.push((phrase.verse, vec![words])),
+        }
+    }
+
+    verses
+}
@@ -0,0 +1,272 @@
+pub mod concordance;
+pub mod fuzzy;
+pub mod history;
+pub mod index;
+pub mod pattern;
+pub mod query;
+pub mod reference;
+pub mod stemmer;
+pub mod versions;
+mod test;
+
+use std::str::FromStr;
+
+use itertools::Itertools;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::{bible::{Bible, VerseRange, WordRange}, canon};
+
+pub use concordance::{get_concordance, get_word_frequencies, Concordance, WordFrequency};
+pub use index::{normalize_word, SearchIndex};
+pub use query::{tokenize_search, SearchQuery, SearchScope, SearchTerm};
+
+lazy_static::lazy_static! {
+    static ref BOOK_REGEX: Regex = Regex::new(r"^\s*(?<prefix>\d+)?\s*(?<name>[\S][\S\s]*?)?\s*$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SectionSearchResult {
+    pub book: u32,
+    pub chapter: u32,
+    pub verse_range: Option<VerseRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordSearchResult {
+    pub book: u32,
+    pub chapter: u32,
+    pub verse: u32,
+    /// The words matched by the search, in order. A phrase is a single range
+    pub hits: Vec<WordRange>,
+    /// How well the verse matches the search, compared to the other results. Higher is better
+    pub score: f32,
+}
+
+/// The order that word search results are given in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchOrder {
+    /// The order of the verses in the Bible
+    #[default]
+    Canonical,
+    /// The best matches first, by their score
+    Relevance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ParsedSearchResult {
+    Section { section: SectionSearchResult },
+    /// A search for several references, such as `Rom 1:1,5; 2:1`, or a range across chapters, with a section for each chapter
+    Sections { sections: Vec<SectionSearchResult> },
+    // don't send over all the words yet, as we first need to move to another page, then we can load the search from this page
+    // essentially, doing the same thing as the section (key to a search)
+    Word { words: Vec<String> }, 
+    /// `suggestions` are what may have been meant instead, such as the names of books that are close to a misspelled book, best first
+    Error { error: String, suggestions: Vec<String> },
+}
+
+pub fn parse_search(text: &str, bible: &Bible) -> ParsedSearchResult {
+    if text.chars().all(char::is_whitespace) {
+        return ParsedSearchResult::Error {
+            error: "Search must contain a word".into(),
+            suggestions: vec![],
+        };
+    }
+
+    if text.is_empty() {
+        return ParsedSearchResult::Error {
+            error: "Search must contain a word".into(),
+            suggestions: vec![],
+        };
+    }
+
+    // patterns are kept whole, as the only word of the search, so they can be searched for in the same way as words
+    if let Some(pattern) = pattern::parse_pattern(text) {
+        return match pattern {
+            Ok(_) => ParsedSearchResult::Word { words: vec![text.trim().to_owned()] },
+            Err(error) => ParsedSearchResult::Error { error, suggestions: vec![] },
+        };
+    }
+
+    // quoted phrases and scoped searches are always word searches, even if they contain numbers
+    let is_word_search = text.contains('"') || text.to_ascii_lowercase().contains(query::SCOPE_PREFIX);
+    let references = if is_word_search { None } else { reference::parse_references(text, bible) };
+
+    match references {
+        Some(Ok(mut sections)) if sections.len() == 1 => ParsedSearchResult::Section { section: sections.remove(0) },
+        Some(Ok(sections)) => ParsedSearchResult::Sections { sections },
+        Some(Err(BookNameError { error, suggestions })) => ParsedSearchResult::Error { error, suggestions },
+        None => match check_word_search(text) {
+            Ok(words) => ParsedSearchResult::Word { words },
+            Err(error) => ParsedSearchResult::Error { error, suggestions: vec![] },
+        },
+    }
+}
+
+/// Splits a search into its words and operators, checking that they form a valid query
+fn check_word_search(text: &str) -> Result<Vec<String>, String> {
+    let words = tokenize_search(text)?;
+    SearchQuery::parse(&words)?;
+    Ok(words)
+}
+
+/// Runs a search given by `parse_search`, which is either a pattern (see `pattern`), or a list of words
+pub fn run_search(words: &[&str], bible: &Bible, index: &SearchIndex) -> Vec<WordSearchResult> {
+    // a pattern is always the only word of its search
+    let pattern = match words {
+        [word] => pattern::parse_pattern(word).and_then(Result::ok),
+        _ => None,
+    };
+
+    match pattern {
+        Some(pattern) => pattern::search_pattern(&pattern, bible),
+        None => search_bible(words, index),
+    }
+}
+
+/// Finds every verse that matches the words of a search (see `SearchQuery`), in canonical order.
+/// Returns nothing if the words are not a valid query
+pub fn search_bible(words: &[&str], index: &SearchIndex) -> Vec<WordSearchResult> {
+    match SearchQuery::parse(words) {
+        Ok(query) => search_query(&query, index),
+        Err(_) => vec![],
+    }
+}
+
+pub fn search_query(query: &SearchQuery, index: &SearchIndex) -> Vec<WordSearchResult> {
+    let (verses, mut uses) = query.find_verses_and_uses(index);
+    let verse_count = index.verse_count().max(1) as f32;
+
+    // the uses of each term in its verses, with its inverse document frequency,
+    // so that rarer terms count for more when scoring. Every term was already looked up to find the verses
+    let term_uses = query
+        .get_terms()
+        .into_iter()
+        .unique()
+        .map(|term| {
+            let uses = uses.remove(term).unwrap_or_default();
+            let idf = (verse_count / uses.len().max(1) as f32).ln() + 1.0;
+            (idf, uses)
+        })
+        .collect_vec();
+
+    verses.into_iter().filter_map(|verse_number| {
+        let verse = index.get_verse(verse_number)?;
+
+        let mut hits = vec![];
+        let mut score = 0.0;
+        for (idf, uses) in &term_uses {
+            let Ok(i) = uses.binary_search_by_key(&verse_number, |(v, _)| *v) else {
+                continue;
+            };
+
+            let words = &uses[i].1;
+            hits.extend(words.iter().copied());
+            score += (1.0 + (words.len() as f32).ln()) * idf;
+        }
+
+        hits.sort();
+        hits.dedup();
+
+        Some(WordSearchResult {
+            book: verse.book,
+            chapter: verse.chapter,
+            verse: verse.verse,
+            hits: hits.into_iter().map(|(word_start, word_end)| WordRange {
+                verse_start: verse.verse,
+                word_start,
+                verse_end: verse.verse,
+                word_end,
+            }).collect(),
+            // shorter verses are more about what was searched for
+            score: score / (verse.word_count.max(1) as f32).sqrt(),
+        })
+    }).collect()
+}
+
+/// Sorts results from `search_bible`, which are in canonical order to begin with. Results with the same score keep their order
+pub fn sort_results(results: &mut [WordSearchResult], order: SearchOrder) {
+    match order {
+        SearchOrder::Canonical => results.sort_by_key(|r| (r.book, r.chapter, r.verse)),
+        SearchOrder::Relevance => results.sort_by(|a, b| b.score.total_cmp(&a.score)),
+    }
+}
+
+/// A book name that does not match any book, with the names of the books that were most likely meant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookNameError {
+    pub error: String,
+    pub suggestions: Vec<String>,
+}
+
+/// Finds a book of the Bible from any of its names, abbreviations or ids (see `canon`), or from the start of its name in the Bible.
+/// If there is no such book, suggests the books with the closest names
+pub fn get_book_from_name(prefix: Option<u32>, book_name: &str, bible: &Bible) -> Result<BookTitleData, BookNameError> 
+{
+    let book_name = book_name.trim().to_ascii_lowercase();
+    let book_data = get_book_title_data(bible);
+
+    let full_name = match prefix {
+        Some(prefix) => format!("{} {}", prefix, book_name),
+        None => book_name.clone(),
+    };
+
+    if let Some(index) = canon::find_book(&full_name).and_then(|b| bible.get_book_index(b.id)) {
+        return Ok(book_data[index as usize].clone());
+    }
+
+    // the start of a name, which may be in the language of the Bible
+    let possible_books = book_data
+        .iter()
+        .filter(|b| b.name.starts_with(&book_name))
+        .collect_vec();
+
+    possible_books
+        .iter()
+        .find(|b| b.prefix == prefix)
+        .or_else(|| possible_books.first())
+        .map(|b| (**b).clone())
+        .ok_or_else(|| {
+            let suggestions = fuzzy::suggest_books(prefix, &book_name, bible);
+            let error = match suggestions.first() {
+                Some(suggestion) => format!("The book of `{}`, does not exist. Did you mean `{}`?", book_name, suggestion),
+                None => format!("The book of `{}`, does not exist", book_name),
+            };
+
+            BookNameError { error, suggestions }
+        })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookTitleData {
+    pub prefix: Option<u32>,
+    pub name: String,
+    pub index: u32,
+}
+
+fn get_book_title_data(bible: &Bible) -> Vec<BookTitleData> {
+    bible
+        .books
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let captures = BOOK_REGEX.captures(&b.name).unwrap();
+            let prefix: Option<u32> = load_capture(&captures, "prefix");
+            let name = captures.name("name").unwrap().as_str().to_ascii_lowercase();
+            BookTitleData {
+                prefix,
+                name,
+                index: i as u32,
+            }
+        })
+        .collect_vec()
+}
+
+fn load_capture<T>(captures: &Captures, name: &str) -> Option<T>
+where
+    T: FromStr,
+{
+    captures.name(name).and_then(|c| c.as_str().parse().ok())
+}
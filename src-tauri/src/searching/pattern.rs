@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::bible::{Bible, WordRange};
+
+use super::WordSearchResult;
+
+// Searches written as `/pattern/`, or `/pattern/i` to ignore case, are regular expressions that are matched against the text
+// of each verse, with its words separated by single spaces, such as `/\bLord of \w+s\b/`. A match can not cross from one
+// verse into the next.
+//
+// The regex crate matches in linear time, so no pattern can take exponentially long. As a large Bible still takes a while
+// to go through, a search stops once it has found `MAX_PATTERN_RESULTS` verses, or has run for `PATTERN_SEARCH_TIMEOUT`,
+// giving the verses found so far
+
+/// The most verses that a pattern search gives
+pub const MAX_PATTERN_RESULTS: usize = 1000;
+
+/// How long a pattern search can run for before it stops
+pub const PATTERN_SEARCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The most memory, in bytes, a compiled pattern can use, so that patterns such as `\w{1000}` are rejected
+const MAX_PATTERN_SIZE: usize = 1 << 20;
+
+/// If a search is a pattern, gives the pattern, or an error if it is not a valid pattern. Gives None for any other search
+pub fn parse_pattern(text: &str) -> Option<Result<Regex, String>>
+{
+    let text = text.trim();
+    let (pattern, flags) = text.strip_prefix('/')?.rsplit_once('/')?;
+    if !flags.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return None;
+    }
+
+    if pattern.is_empty()
+    {
+        return Some(Err("A pattern must not be empty, such as `/Lord of \\w+s/`".into()));
+    }
+
+    let mut builder = RegexBuilder::new(pattern);
+    builder.size_limit(MAX_PATTERN_SIZE).dfa_size_limit(MAX_PATTERN_SIZE);
+    for flag in flags.chars()
+    {
+        match flag
+        {
+            'i' => { builder.case_insensitive(true); },
+            _ => return Some(Err(format!("`{}` is not a pattern flag, the only flag is `i` to ignore case", flag))),
+        }
+    }
+
+    Some(builder.build().map_err(|e| format!("Invalid pattern: {}", e)))
+}
+
+/// Finds every verse with a match for the pattern, in canonical order, with each match as a hit. Verses are scored by their
+/// number of matches
+pub fn search_pattern(pattern: &Regex, bible: &Bible) -> Vec<WordSearchResult>
+{
+    search_pattern_until(pattern, bible, Instant::now() + PATTERN_SEARCH_TIMEOUT).0
+}
+
+/// Like `search_pattern`, but runs until `deadline` instead of for `PATTERN_SEARCH_TIMEOUT`. Also gives true if the search
+/// stopped before the end of the Bible, so verses after the last result may match as well
+pub fn search_pattern_until(pattern: &Regex, bible: &Bible, deadline: Instant) -> (Vec<WordSearchResult>, bool)
+{
+    let mut results = vec![];
+    for (book_index, book) in bible.books.iter().enumerate()
+    {
+        for (chapter_index, chapter) in book.chapters.iter().enumerate()
+        {
+            for (verse_index, verse) in chapter.verses.iter().enumerate()
+            {
+                if results.len() >= MAX_PATTERN_RESULTS || Instant::now() >= deadline
+                {
+                    return (results, true);
+                }
+
+                // the byte range of each word in the text
+                let mut text = String::new();
+                let mut words = vec![];
+                for word in &verse.words
+                {
+                    if !text.is_empty() { text.push(' '); }
+
+                    let start = text.len();
+                    text.push_str(word.text.trim());
+                    words.push(start..text.len());
+                }
+
+                let verse_number = verse_index as u32;
+                let mut hits = pattern.find_iter(&text)
+                    .filter(|m| !m.is_empty())
+                    .filter_map(|m| {
+                        let word_start = words.iter().position(|w| w.end > m.start())?;
+                        let word_end = words.iter().rposition(|w| w.start < m.end())?;
+                        Some(WordRange {
+                            verse_start: verse_number,
+                            word_start: word_start as u32,
+                            verse_end: verse_number,
+                            word_end: word_end as u32,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                // matches within the same word are the same hit
+                hits.dedup();
+                if hits.is_empty() { continue; }
+
+                results.push(WordSearchResult {
+                    book: book_index as u32,
+                    chapter: chapter_index as u32,
+                    verse: verse_number,
+                    score: hits.len() as f32,
+                    hits,
+                });
+            }
+        }
+    }
+
+    (results, false)
+}
@@ -0,0 +1,538 @@
+use std::{collections::HashMap, iter::Peekable};
+
+use itertools::{EitherOrBoth, Itertools};
+
+use crate::{bible::Testament, canon};
+
+use super::index::{self, normalize_pattern, IndexedBook, SearchIndex};
+
+// A word search is made of words and quoted phrases, which can be combined with these operators, from the tightest binding:
+//   `NOT word` or `-word`   verses without the word
+//   `a AND b` or `a b`      verses with both
+//   `a OR b`                verses with either
+//   `( ... )`               grouping
+// Operators are only recognized in capitals, so that `and`, `or` and `not` can still be searched for as words.
+// Any word (including those in phrases) can also be a pattern, such as `believ*` or `~believe`, see `SearchIndex::find_word`.
+//
+// A search can be limited to some of the Bible with `in:`, which is combined like any other word, so `grace in:Romans`
+// only finds `grace` in Romans. The scope can be a book (`in:Rom`, or `in:"1 Corinthians"`), a range of books in
+// canonical order (`in:Gen-Deut`), or a testament (`in:OT` or `in:NT`)
+
+const AND: &str = "AND";
+const OR: &str = "OR";
+const NOT: &str = "NOT";
+pub const SCOPE_PREFIX: &str = "in:";
+
+/// The uses of each term of a query, grouped by verse (see `index::group_verses`)
+pub type TermUses<'a> = HashMap<&'a SearchTerm, Vec<(u32, Vec<(u32, u32)>)>>;
+
+/// A part of a word search. A phrase is written in quotes, and only matches its words one after another, in order
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SearchTerm
+{
+    Word(String),
+    Phrase(Vec<String>),
+}
+
+impl SearchTerm
+{
+    /// Parses a word given by `tokenize_search`, normalizing its words with `normalize_pattern`
+    pub fn parse(word: &str) -> Self
+    {
+        match word.strip_prefix('"').and_then(|w| w.strip_suffix('"'))
+        {
+            Some(phrase) => Self::Phrase(phrase.split_whitespace().map(normalize_pattern).filter(|w| !w.is_empty()).collect()),
+            None => Self::Word(normalize_pattern(word)),
+        }
+    }
+
+    pub fn words(&self) -> &[String]
+    {
+        match self
+        {
+            Self::Word(word) => std::slice::from_ref(word),
+            Self::Phrase(words) => words,
+        }
+    }
+
+    /// Checks that every word has a letter, and that patterns only use either `~` or `*`
+    pub fn check_words(&self) -> Result<(), String>
+    {
+        if self.words().is_empty()
+        {
+            return Err("Search must contain a word".into());
+        }
+
+        for word in self.words()
+        {
+            if !word.chars().any(char::is_alphanumeric)
+            {
+                return Err(format!("`{}` is not a word", word));
+            }
+
+            if word.starts_with('~') && word.contains('*')
+            {
+                return Err(format!("`{}` can not use both `~` and `*`", word));
+            }
+
+            if word.rfind('~').is_some_and(|i| i > 0)
+            {
+                return Err(format!("`~` must be at the start of a word, such as `~{}`", word.replace('~', "")));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The verses that use the term at least `frequency` times, by their position in the index. The uses of the term are
+    /// only found the first time, and kept in `uses`
+    fn find_verses<'a>(&'a self, frequency: usize, index: &SearchIndex, uses: &mut TermUses<'a>) -> Vec<u32>
+    {
+        uses.entry(self)
+            .or_insert_with(|| index::group_verses(&index.find_phrase(self.words())))
+            .iter()
+            .filter(|(_, uses)| uses.len() >= frequency)
+            .map(|(verse, _)| *verse)
+            .collect()
+    }
+}
+
+/// A part of the Bible that a search is limited to. Books are given by their canonical id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope
+{
+    Testament(Testament),
+    Books
+    {
+        start: &'static str,
+        end: &'static str,
+    },
+}
+
+impl SearchScope
+{
+    /// Parses the scope of an `in:` word, without the prefix
+    pub fn parse(scope: &str) -> Result<Self, String>
+    {
+        let scope = scope.trim_matches('"').trim();
+        match scope.to_ascii_lowercase().as_str()
+        {
+            "ot" | "old" => return Ok(Self::Testament(Testament::Old)),
+            "nt" | "new" => return Ok(Self::Testament(Testament::New)),
+            "" => return Err(format!("`{}` must be followed by a book or testament, such as `{}NT`", SCOPE_PREFIX, SCOPE_PREFIX)),
+            _ => {},
+        }
+
+        let find_book = |name: &str| {
+            canon::find_book(name).map(|b| b.id).ok_or(format!("The book of `{}`, does not exist", name.trim()))
+        };
+
+        // `1-2 Sam` is not supported, as the `-` is part of the range
+        let (start, end) = match scope.split_once('-')
+        {
+            Some((start, end)) => (find_book(start)?, find_book(end)?),
+            None => {
+                let book = find_book(scope)?;
+                (book, book)
+            }
+        };
+
+        // a backwards range is taken to mean the same books
+        if canon::get_book_order(start) > canon::get_book_order(end)
+        {
+            return Ok(Self::Books { start: end, end: start });
+        }
+
+        Ok(Self::Books { start, end })
+    }
+
+    pub fn contains(&self, book: &IndexedBook) -> bool
+    {
+        match self
+        {
+            Self::Testament(testament) => book.testament == *testament,
+            Self::Books { start, end } => {
+                let order = canon::get_book_order(&book.id);
+                order.is_some() && canon::get_book_order(start) <= order && order <= canon::get_book_order(end)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQuery
+{
+    Term(SearchTerm),
+    /// Matches every verse in the scope, so it can only be used to limit other queries
+    Scope(SearchScope),
+    /// Repeated terms must be used as many times as they are repeated, so `the the` only matches verses with two `the`s
+    And(Vec<SearchQuery>),
+    Or(Vec<SearchQuery>),
+    Not(Box<SearchQuery>),
+}
+
+impl SearchQuery
+{
+    /// Parses the words of a search, as split up by `tokenize_search`
+    pub fn parse<S>(words: &[S]) -> Result<Self, String>
+        where S : AsRef<str>
+    {
+        let mut parser = QueryParser {
+            tokens: words.iter().map(|w| w.as_ref()).collect(),
+            position: 0,
+            depth: 0,
+        };
+
+        let query = parser.parse_or()?;
+        if let Some(token) = parser.peek()
+        {
+            return Err(format!("Unexpected `{}`, with no `(` before it", token));
+        }
+
+        if !query.is_bounded()
+        {
+            return Err("A search must have a word that is not excluded, such as `love -world`".into());
+        }
+
+        Ok(query)
+    }
+
+    /// Every term that a matched verse can have, leaving out any that are excluded
+    pub fn get_terms(&self) -> Vec<&SearchTerm>
+    {
+        match self
+        {
+            Self::Term(term) => vec![term],
+            Self::And(queries) | Self::Or(queries) => queries.iter().flat_map(|q| q.get_terms()).collect(),
+            Self::Scope(_) | Self::Not(_) => vec![],
+        }
+    }
+
+    /// Finds the verses that match the query, by their position in the index, in canonical order
+    pub fn find_verses(&self, index: &SearchIndex) -> Vec<u32>
+    {
+        self.find_verses_and_uses(index).0
+    }
+
+    /// Like `find_verses`, but also gives the uses of every term that was looked up, which includes all of `get_terms`
+    pub fn find_verses_and_uses(&self, index: &SearchIndex) -> (Vec<u32>, TermUses<'_>)
+    {
+        let mut uses = HashMap::new();
+        let verses = match self.find_verse_set(index, &mut uses)
+        {
+            VerseSet::Only(verses) => verses,
+            VerseSet::Except(_) => vec![], // the query would match almost every verse, which `parse` does not allow
+        };
+
+        (verses, uses)
+    }
+
+    /// If the query can only match verses that have some word, rather than every verse without some word
+    fn is_bounded(&self) -> bool
+    {
+        match self
+        {
+            Self::Term(_) => true,
+            Self::And(queries) => queries.iter().any(|q| q.is_bounded()),
+            Self::Or(queries) => queries.iter().all(|q| q.is_bounded()),
+            Self::Scope(_) | Self::Not(_) => false,
+        }
+    }
+
+    fn find_verse_set<'a>(&'a self, index: &SearchIndex, uses: &mut TermUses<'a>) -> VerseSet
+    {
+        match self
+        {
+            Self::Term(term) => VerseSet::Only(term.find_verses(1, index, uses)),
+            Self::Scope(scope) => VerseSet::Only(index.books().iter().filter(|b| scope.contains(b)).flat_map(|b| b.verses.clone()).collect()),
+            Self::And(queries) => {
+                let terms = queries.iter().filter_map(|q| match q
+                {
+                    Self::Term(term) => Some(term),
+                    _ => None,
+                }).counts();
+
+                let term_sets = terms.into_iter().map(|(term, frequency)| VerseSet::Only(term.find_verses(frequency, index, uses))).collect_vec();
+                let query_sets = queries.iter().filter(|q| !matches!(q, Self::Term(_))).map(|q| q.find_verse_set(index, uses)).collect_vec();
+
+                term_sets.into_iter().chain(query_sets).fold(VerseSet::Except(vec![]), VerseSet::and)
+            },
+            Self::Or(queries) => queries.iter().map(|q| q.find_verse_set(index, uses)).fold(VerseSet::Only(vec![]), VerseSet::or),
+            Self::Not(query) => match query.find_verse_set(index, uses)
+            {
+                VerseSet::Only(verses) => VerseSet::Except(verses),
+                VerseSet::Except(verses) => VerseSet::Only(verses),
+            },
+        }
+    }
+}
+
+/// Splits a search into its words and operators. Each quoted phrase is kept together (with its quotes) as a single word,
+/// and joining the words with spaces gives back an equivalent search
+pub fn tokenize_search(text: &str) -> Result<Vec<String>, String>
+{
+    let mut tokens = vec![];
+    let mut token = String::new();
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next()
+    {
+        match c
+        {
+            c if c.is_whitespace() => push_token(&mut tokens, &mut token),
+            ':' if token.trim_start_matches('-').eq_ignore_ascii_case(&SCOPE_PREFIX[..SCOPE_PREFIX.len() - 1]) => {
+                token.push(c);
+
+                // a scope can be quoted, for books with spaces in their name
+                if chars.peek() == Some(&'"')
+                {
+                    chars.next();
+                    let book = read_quoted(&mut chars)?;
+                    token.push_str(&format!("\"{}\"", book));
+                    push_token(&mut tokens, &mut token);
+                }
+            },
+            '(' | ')' => {
+                push_token(&mut tokens, &mut token);
+                tokens.push(c.to_string());
+            },
+            '"' => {
+                // only a `-` can be joined to a phrase
+                if token != "-"
+                {
+                    push_token(&mut tokens, &mut token);
+                }
+
+                let phrase = read_quoted(&mut chars)?;
+                token.push_str(&format!("\"{}\"", phrase));
+                push_token(&mut tokens, &mut token);
+            },
+            c if is_word_char(c) || c == '-' => token.push(c),
+            c => return Err(format!("`{}` can not be searched for, searched words can only be words or numbers", c)),
+        }
+    }
+
+    push_token(&mut tokens, &mut token);
+    Ok(tokens)
+}
+
+/// Letters and numbers, as well as the apostrophes of words such as `Lord's`, and the characters of patterns
+fn is_word_char(c: char) -> bool
+{
+    c.is_ascii_alphanumeric() || matches!(c, '\'' | '*' | '~')
+}
+
+/// Reads the rest of a quoted phrase, after its opening `"`, joining any whitespace
+fn read_quoted(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<String, String>
+{
+    let mut phrase = String::new();
+    loop
+    {
+        match chars.next()
+        {
+            Some('"') => break,
+            Some(c) if is_word_char(c) || c.is_whitespace() => phrase.push(c),
+            Some(c) => return Err(format!("`{}` can not be searched for, searched words can only be words or numbers", c)),
+            None => return Err("A quoted phrase is missing its closing `\"`".into()),
+        }
+    }
+
+    let phrase = phrase.split_whitespace().join(" ");
+    if phrase.is_empty()
+    {
+        return Err("A quoted phrase must contain a word".into());
+    }
+
+    Ok(phrase)
+}
+
+fn push_token(tokens: &mut Vec<String>, token: &mut String)
+{
+    if !token.is_empty()
+    {
+        tokens.push(std::mem::take(token));
+    }
+}
+
+struct QueryParser<'a>
+{
+    tokens: Vec<&'a str>,
+    position: usize,
+    depth: u32,
+}
+
+impl<'a> QueryParser<'a>
+{
+    fn peek(&self) -> Option<&'a str>
+    {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str>
+    {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    /// If the next token can not start a query
+    fn is_at_end(&self) -> bool
+    {
+        matches!(self.peek(), None | Some(")") | Some(OR) | Some(AND))
+    }
+
+    fn parse_or(&mut self) -> Result<SearchQuery, String>
+    {
+        let mut queries = vec![self.parse_and()?];
+        while self.peek() == Some(OR)
+        {
+            self.next();
+            if self.is_at_end()
+            {
+                return Err(format!("`{}` must be followed by a word", OR));
+            }
+
+            queries.push(self.parse_and()?);
+        }
+
+        Ok(if queries.len() == 1 { queries.remove(0) } else { SearchQuery::Or(queries) })
+    }
+
+    fn parse_and(&mut self) -> Result<SearchQuery, String>
+    {
+        let mut queries = vec![];
+        loop
+        {
+            match self.peek()
+            {
+                None | Some(")") | Some(OR) => break,
+                Some(AND) => {
+                    self.next();
+                    if queries.is_empty() || self.is_at_end()
+                    {
+                        return Err(format!("`{}` must be between two words", AND));
+                    }
+                },
+                Some(_) => queries.push(self.parse_not()?),
+            }
+        }
+
+        match self.peek()
+        {
+            _ if !queries.is_empty() => {},
+            Some(OR) => return Err(format!("`{}` must be between two words", OR)),
+            Some(")") if self.depth > 0 => return Err("Parentheses must contain a word".into()),
+            Some(")") => return Err("Unexpected `)`, with no `(` before it".into()),
+            _ => return Err("Search must contain a word".into()),
+        }
+
+        Ok(if queries.len() == 1 { queries.remove(0) } else { SearchQuery::And(queries) })
+    }
+
+    fn parse_not(&mut self) -> Result<SearchQuery, String>
+    {
+        let Some(token) = self.next() else {
+            return Err("Search must contain a word".into());
+        };
+
+        if token == NOT
+        {
+            if self.is_at_end()
+            {
+                return Err(format!("`{}` must be followed by a word", NOT));
+            }
+
+            return Ok(SearchQuery::Not(Box::new(self.parse_not()?)));
+        }
+
+        if token == "("
+        {
+            self.depth += 1;
+            let query = self.parse_or()?;
+            self.depth -= 1;
+
+            if self.next() != Some(")")
+            {
+                return Err("Missing a closing `)`".into());
+            }
+
+            return Ok(query);
+        }
+
+        if let Some(word) = token.strip_prefix('-')
+        {
+            if word.is_empty()
+            {
+                return Err("`-` must be followed by a word, such as `-world`".into());
+            }
+
+            return Ok(SearchQuery::Not(Box::new(parse_word(word)?)));
+        }
+
+        parse_word(token)
+    }
+}
+
+fn parse_word(word: &str) -> Result<SearchQuery, String>
+{
+    let is_scope = word.get(..SCOPE_PREFIX.len()).is_some_and(|p| p.eq_ignore_ascii_case(SCOPE_PREFIX));
+    if is_scope
+    {
+        return Ok(SearchQuery::Scope(SearchScope::parse(&word[SCOPE_PREFIX.len()..])?));
+    }
+
+    parse_term(word)
+}
+
+fn parse_term(word: &str) -> Result<SearchQuery, String>
+{
+    let term = SearchTerm::parse(word);
+    if term.words().iter().all(|w| w.is_empty())
+    {
+        return Err(format!("`{}` is not a word", word));
+    }
+
+    term.check_words()?;
+    Ok(SearchQuery::Term(term))
+}
+
+/// A set of verses, by their position in the index. As excluding words gives almost every verse, those sets are
+/// stored by the verses that they do not have. Both lists are sorted
+enum VerseSet
+{
+    Only(Vec<u32>),
+    Except(Vec<u32>),
+}
+
+impl VerseSet
+{
+    fn and(self, other: Self) -> Self
+    {
+        match (self, other)
+        {
+            (Self::Only(a), Self::Only(b)) => Self::Only(merge(&a, &b, |e| matches!(e, EitherOrBoth::Both(..)))),
+            (Self::Only(a), Self::Except(b)) | (Self::Except(b), Self::Only(a)) => Self::Only(merge(&a, &b, |e| matches!(e, EitherOrBoth::Left(_)))),
+            (Self::Except(a), Self::Except(b)) => Self::Except(merge(&a, &b, |_| true)),
+        }
+    }
+
+    fn or(self, other: Self) -> Self
+    {
+        match (self, other)
+        {
+            (Self::Only(a), Self::Only(b)) => Self::Only(merge(&a, &b, |_| true)),
+            (Self::Only(a), Self::Except(b)) | (Self::Except(b), Self::Only(a)) => Self::Except(merge(&b, &a, |e| matches!(e, EitherOrBoth::Left(_)))),
+            (Self::Except(a), Self::Except(b)) => Self::Except(merge(&a, &b, |e| matches!(e, EitherOrBoth::Both(..)))),
+        }
+    }
+}
+
+/// Merges two sorted lists, keeping the verses where `keep` is true, depending on which of the lists they are in
+fn merge(a: &[u32], b: &[u32], keep: impl Fn(&EitherOrBoth<&u32, &u32>) -> bool) -> Vec<u32>
+{
+    a.iter()
+        .merge_join_by(b.iter(), |a, b| a.cmp(b))
+        .filter(|e| keep(e))
+        .map(|e| *e.reduce(|a, _| a))
+        .collect()
+}
@@ -0,0 +1,355 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::bible::{Bible, VerseRange};
+
+use super::{get_book_from_name, BookNameError, SectionSearchResult};
+
+// Parses Bible references, such as `John 3:16`. A search is a list of passages separated by `;`, where each passage is
+// a book followed by a list of chapters or verses separated by `,`. The book can be left out to use the book of the passage before.
+//
+//   `1 Cor 13`                 a whole chapter. The book can have a number, or a roman numeral (`II Kings`) before its name
+//   `Gen 1-3`                  a range of chapters
+//   `John 3:16`, `John 3.16`   a verse
+//   `Rom 1:1,5,7-9; 2:1`       verses in a chapter, then a verse in the next chapter of the same book
+//   `John 3:16-4:2`            a range across chapters, which is split up into a section for each chapter
+//
+// Book names can be any name, abbreviation or id known to `canon`, or the start of the name of a book in the Bible.
+// Chapter and verse numbers are 1 based, as they are written
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token
+{
+    Number(u32),
+    Word(String),
+    Colon,
+    Dash,
+    Comma,
+    Semicolon,
+}
+
+/// A chapter, or a verse in a chapter, as written in the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position
+{
+    chapter: u32,
+    verse: Option<u32>,
+}
+
+/// A chapter, verse, or range of either
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reference
+{
+    start: Position,
+    end: Position,
+}
+
+/// The references to a book, with the name of the book as written, which is only looked up once the whole search has been parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Passage
+{
+    prefix: Option<u32>,
+    book: String,
+    references: Vec<Reference>,
+}
+
+/// Parses a search as a list of references, giving a section for each chapter. Returns None if the search is not written as a
+/// reference (so should be searched for as words instead), or an error if it is, but a book does not exist
+pub fn parse_references(text: &str, bible: &Bible) -> Option<Result<Vec<SectionSearchResult>, BookNameError>>
+{
+    let tokens = tokenize_reference(text)?;
+    let mut parser = ReferenceParser {
+        tokens: &tokens,
+        position: 0,
+    };
+
+    let passages = parser.parse_passages()?;
+
+    let mut sections = vec![];
+    for passage in passages
+    {
+        let book = match get_book_from_name(passage.prefix, &passage.book, bible)
+        {
+            Ok(book) => book.index,
+            Err(error) => return Some(Err(error)),
+        };
+
+        sections.extend(passage.references.iter().flat_map(|r| get_sections(book, r, bible)));
+    }
+
+    Some(Ok(sections))
+}
+
+/// Splits a search into the tokens of a reference. Returns None if it has anything that can not be part of a reference
+fn tokenize_reference(text: &str) -> Option<Vec<Token>>
+{
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.peek().copied()
+    {
+        match c
+        {
+            c if c.is_whitespace() => { chars.next(); },
+            c if c.is_ascii_digit() => tokens.push(Token::Number(read_number(&mut chars)?)),
+            c if c.is_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_alphabetic() || *c == '\'')
+                {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            },
+            ':' => { chars.next(); tokens.push(Token::Colon); },
+            // a period after a word ends an abbreviation (`Gen.`), but between numbers separates a chapter and verse (`3.16`)
+            '.' => {
+                chars.next();
+                if matches!(tokens.last(), Some(Token::Number(_)))
+                {
+                    tokens.push(Token::Colon);
+                }
+            },
+            '-' | '–' | '—' => { chars.next(); tokens.push(Token::Dash); },
+            ',' => { chars.next(); tokens.push(Token::Comma); },
+            ';' => { chars.next(); tokens.push(Token::Semicolon); },
+            _ => return None,
+        }
+    }
+
+    Some(tokens)
+}
+
+fn read_number(chars: &mut Peekable<Chars>) -> Option<u32>
+{
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit)
+    {
+        number.push(c);
+    }
+
+    number.parse().ok()
+}
+
+struct ReferenceParser<'a>
+{
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> ReferenceParser<'a>
+{
+    fn peek(&self) -> Option<&'a Token>
+    {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&'a Token>
+    {
+        self.tokens.get(self.position + offset)
+    }
+
+    fn next_if(&mut self, token: &Token) -> bool
+    {
+        let is_next = self.peek() == Some(token);
+        if is_next
+        {
+            self.position += 1;
+        }
+
+        is_next
+    }
+
+    fn next_number(&mut self) -> Option<u32>
+    {
+        match self.peek()
+        {
+            Some(Token::Number(number)) => {
+                self.position += 1;
+                Some(*number)
+            },
+            _ => None,
+        }
+    }
+
+    /// passages := passage (`;` passage)*
+    fn parse_passages(&mut self) -> Option<Vec<Passage>>
+    {
+        let mut passages: Vec<Passage> = vec![];
+        loop
+        {
+            let (prefix, book) = if self.is_at_book()
+            {
+                self.parse_book()?
+            }
+            else
+            {
+                let last = passages.last()?;
+                (last.prefix, last.book.clone())
+            };
+
+            let references = self.parse_chapters()?;
+            passages.push(Passage { prefix, book, references });
+
+            if self.peek().is_none()
+            {
+                return Some(passages);
+            }
+
+            if !self.next_if(&Token::Semicolon)
+            {
+                return None;
+            }
+        }
+    }
+
+    /// If the next tokens are a book, rather than a chapter, such as the `2 Cor` in `Rom 1; 2 Cor 3`
+    fn is_at_book(&self) -> bool
+    {
+        matches!((self.peek(), self.peek_at(1)), (Some(Token::Word(_)), _) | (Some(Token::Number(_)), Some(Token::Word(_))))
+    }
+
+    /// book := (number | roman numeral)? word+
+    fn parse_book(&mut self) -> Option<(Option<u32>, String)>
+    {
+        let mut prefix = self.next_number();
+
+        let mut words = vec![];
+        while let Some(Token::Word(word)) = self.peek()
+        {
+            words.push(word.as_str());
+            self.position += 1;
+        }
+
+        if prefix.is_none() && words.len() > 1
+        {
+            prefix = match words[0].to_ascii_lowercase().as_str()
+            {
+                "i" => Some(1),
+                "ii" => Some(2),
+                "iii" => Some(3),
+                _ => None,
+            };
+
+            if prefix.is_some()
+            {
+                words.remove(0);
+            }
+        }
+
+        if words.is_empty()
+        {
+            return None;
+        }
+
+        Some((prefix, words.join(" ")))
+    }
+
+    /// chapters := item (`,` item)*
+    /// item := number (`:` number)? (`-` number (`:` number)?)?
+    ///
+    /// Once a verse has been given, lone numbers are verses in the same chapter, so `1:1,5` is two verses, but `1,5` is two chapters
+    fn parse_chapters(&mut self) -> Option<Vec<Reference>>
+    {
+        let mut references = vec![];
+        let mut chapter = None;
+        loop
+        {
+            let number = self.next_number()?;
+            let start = if self.next_if(&Token::Colon)
+            {
+                Position { chapter: number, verse: Some(self.next_number()?) }
+            }
+            else
+            {
+                match chapter
+                {
+                    Some(chapter) => Position { chapter, verse: Some(number) },
+                    None => Position { chapter: number, verse: None },
+                }
+            };
+
+            let end = if self.next_if(&Token::Dash)
+            {
+                let number = self.next_number()?;
+                if self.next_if(&Token::Colon)
+                {
+                    Position { chapter: number, verse: Some(self.next_number()?) }
+                }
+                else if start.verse.is_some()
+                {
+                    Position { chapter: start.chapter, verse: Some(number) }
+                }
+                else
+                {
+                    Position { chapter: number, verse: None }
+                }
+            }
+            else
+            {
+                start
+            };
+
+            if end.verse.is_some()
+            {
+                chapter = Some(end.chapter);
+            }
+
+            references.push(Reference { start, end });
+
+            if !self.next_if(&Token::Comma)
+            {
+                return Some(references);
+            }
+        }
+    }
+}
+
+/// Gets the sections of the Bible that a reference is for, with a section for each chapter. A chapter that does not exist
+/// goes to the first chapter of the book, and verses that do not exist are left out, going to the whole chapter
+fn get_sections(book_index: u32, reference: &Reference, bible: &Bible) -> Vec<SectionSearchResult>
+{
+    let book = &bible.books[book_index as usize];
+    let chapter_count = book.chapters.len() as u32;
+
+    let start = reference.start.chapter;
+    if start == 0 || start > chapter_count
+    {
+        return vec![SectionSearchResult { book: book_index, chapter: 0, verse_range: None }];
+    }
+
+    let end = reference.end.chapter.clamp(start, chapter_count);
+
+    (start..=end).map(|number| {
+        let verse_count = book.chapters[number as usize - 1].verses.len() as u32;
+        let verse_start = if number == start { reference.start.verse } else { None };
+        let verse_end = if number == reference.end.chapter { reference.end.verse } else { None };
+
+        let verse_range = match (verse_start, verse_end)
+        {
+            (None, None) => None,
+            // from the start of the chapter, to a verse
+            (None, Some(verse_end)) => get_verse_range(1, verse_end, verse_count),
+            // from a verse, to the end of the chapter, or the given verse
+            (Some(verse_start), verse_end) => get_verse_range(verse_start, verse_end.unwrap_or(verse_count), verse_count),
+        };
+
+        SectionSearchResult {
+            book: book_index,
+            chapter: number - 1,
+            verse_range,
+        }
+    }).collect()
+}
+
+/// Converts 1 based verse numbers into a range. The end is moved to be in the chapter, and to not be before the start.
+/// Gives None if the first verse does not exist
+fn get_verse_range(start: u32, end: u32, verse_count: u32) -> Option<VerseRange>
+{
+    if start == 0 || start > verse_count
+    {
+        return None;
+    }
+
+    Some(VerseRange {
+        start: start - 1,
+        end: end.clamp(start, verse_count) - 1,
+    })
+}
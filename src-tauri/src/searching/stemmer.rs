@@ -0,0 +1,95 @@
+// A light stemmer for English, that only removes the endings of inflected words, including the archaic endings of
+// Early Modern English used by the KJV, so that `believe`, `believed`, `believeth`, `believest` and `believing` all have
+// the same stem. The stems are not real roots of the words, and are only meant to be compared with each other
+
+/// Common irregular forms, with the word that they are a form of
+const IRREGULAR_FORMS: &[(&str, &str)] = &[
+    ("hath", "have"),
+    ("hast", "have"),
+    ("had", "have"),
+    ("hadst", "have"),
+    ("having", "have"),
+    ("doth", "do"),
+    ("dost", "do"),
+    ("did", "do"),
+    ("didst", "do"),
+    ("done", "do"),
+    ("saith", "say"),
+    ("said", "say"),
+    ("saidst", "say"),
+    ("shalt", "shall"),
+    ("wilt", "will"),
+    ("canst", "can"),
+    ("couldest", "could"),
+    ("wouldest", "would"),
+    ("shouldest", "should"),
+];
+
+/// Suffixes, from longest to shortest, with what they are replaced by
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ieth", "y"),
+    ("iest", "y"),
+    ("edst", ""),
+    ("ies", "y"),
+    ("ied", "y"),
+    ("eth", ""),
+    ("est", ""),
+    ("ing", ""),
+    ("ed", ""),
+    ("es", ""),
+    ("s", ""),
+];
+
+/// Double consonants that are part of the stem, rather than doubled for a suffix (as in `sinneth`)
+const KEPT_DOUBLES: &[char] = &['l', 's', 'f', 'z'];
+
+/// Gets the stem of a word that has been normalized with `normalize_word`
+pub fn stem_word(word: &str) -> String
+{
+    let word = word.strip_suffix("'s").unwrap_or(word).trim_end_matches('\'');
+    let word = IRREGULAR_FORMS.iter().find(|(form, _)| *form == word).map_or(word, |(_, base)| base);
+
+    let mut stem = strip_suffix(word).unwrap_or_else(|| word.to_owned());
+
+    let mut chars = stem.chars().rev();
+    if let (Some(last), Some(before)) = (chars.next(), chars.next())
+    {
+        if last == before && !is_vowel(last) && !KEPT_DOUBLES.contains(&last) && stem.len() >= 3
+        {
+            stem.pop();
+        }
+    }
+
+    if stem.len() >= 3 && stem.ends_with('e')
+    {
+        stem.pop();
+    }
+
+    stem
+}
+
+fn strip_suffix(word: &str) -> Option<String>
+{
+    SUFFIXES.iter().find_map(|(suffix, replacement)| {
+        let stem = word.strip_suffix(suffix)?;
+
+        // the rest must still look like a word, so that words such as `thing`, `west` and `this` are left alone
+        let min_length = if replacement.is_empty() { 2 } else { 3 };
+        if stem.chars().count() < min_length || !stem.chars().any(is_vowel)
+        {
+            return None;
+        }
+
+        match *suffix
+        {
+            "s" if stem.ends_with(['s', 'u', 'i']) || stem.chars().count() < 3 => None,
+            "ed" if stem.ends_with('e') => None,
+            _ => Some(format!("{}{}", stem, replacement)),
+        }
+    })
+}
+
+fn is_vowel(c: char) -> bool
+{
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
@@ -0,0 +1,493 @@
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::bible::{Bible, Testament, VerseRange};
+    use crate::bible_parsing::parse_bible;
+    use crate::searching::{history::{SearchHistory, MAX_RECENT_SEARCHES}, fuzzy::edit_distance, versions::search_versions, pattern::{parse_pattern, search_pattern, MAX_PATTERN_RESULTS}, get_concordance, get_word_frequencies, SectionSearchResult, normalize_word, stemmer::stem_word, parse_search, search_bible, sort_results, SearchOrder, tokenize_search, ParsedSearchResult, SearchIndex, SearchQuery, SearchScope, SearchTerm};
+
+    fn load_kjv() -> Bible {
+        parse_bible(include_str!("../../resources/bibles/small_kjv.txt")).unwrap()
+    }
+
+    /// Finds the verses with all the words by checking every verse, as the index should give the same results
+    fn scan_bible(words: &[&str], bible: &Bible) -> Vec<(u32, u32, u32)> {
+        let frequencies = words.iter().map(|w| normalize_word(w)).counts();
+
+        let mut results = vec![];
+        for (b, book) in bible.books.iter().enumerate() {
+            for (c, chapter) in book.chapters.iter().enumerate() {
+                for (v, verse) in chapter.verses.iter().enumerate() {
+                    let counts = verse.words.iter().map(|w| normalize_word(&w.text)).counts();
+                    if frequencies.iter().all(|(w, f)| counts.get(w).is_some_and(|c| c >= f)) {
+                        results.push((b as u32, c as u32, v as u32));
+                    }
+                }
+            }
+        }
+
+        results
+    }
+
+    #[test]
+    fn test_search_index() {
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+
+        assert_eq!(index.verse_count() as usize, bible.books.iter().flat_map(|b| &b.chapters).map(|c| c.verses.len()).sum::<usize>());
+
+        // the first word of the Bible, with its punctuation and case ignored
+        let postings = index.get_postings("In,");
+        assert_eq!((postings[0].verse, postings[0].word), (0, 0));
+        assert!(postings.windows(2).all(|p| p[0] < p[1]));
+        assert!(index.get_postings("notaword").is_empty());
+
+        for query in [&["God"][..], &["god", "created"], &["the", "the", "the"], &["LIGHT"], &["darkness", "notaword"]] {
+            let results = search_bible(query, &index).iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec();
+            assert_eq!(results, scan_bible(query, &bible), "searching for {:?}", query);
+        }
+    }
+
+    #[test]
+    fn test_phrase_search() {
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+
+        match parse_search("\"the  Spirit of God\" waters", &bible) {
+            ParsedSearchResult::Word { words } => assert_eq!(words, vec!["\"the Spirit of God\"", "waters"]),
+            other => panic!("expected a word search, found {:?}", other),
+        }
+
+        match parse_search("\"the Spirit", &bible) {
+            ParsedSearchResult::Error { .. } => {},
+            other => panic!("expected an error, found {:?}", other),
+        }
+
+        let results = search_bible(&["\"the Spirit of God\"", "waters"], &index);
+        assert_eq!(results.len(), 1);
+
+        let result = &results[0];
+        assert_eq!((result.book, result.chapter, result.verse), (0, 0, 1));
+        assert_eq!(result.hits.len(), 2); // the phrase, then `waters`
+
+        let verse = &bible.books[0].chapters[0].verses[1];
+        let matched = result.hits.iter().map(|hit| {
+            verse.words[hit.word_start as usize..=hit.word_end as usize].iter().map(|w| normalize_word(&w.text)).collect_vec()
+        }).collect_vec();
+        assert_eq!(matched, vec![vec!["the", "spirit", "of", "god"], vec!["waters"]]);
+
+        // the words are all in the verse, but not in this order
+        assert!(search_bible(&["\"God of Spirit the\""], &index).is_empty());
+        assert_eq!(search_bible(&["God", "of", "Spirit", "the"], &index).len(), scan_bible(&["God", "of", "Spirit", "the"], &bible).len());
+    }
+
+    #[test]
+    fn test_phrase_search_punctuation() {
+        let bible = parse_bible("TEST\n\nGenesis 1:1 a  b - c, d\n").unwrap();
+        let index = SearchIndex::new(&bible);
+
+        // words that are only punctuation or whitespace are not counted, so `a`, `b`, `c` and `d` are all one after another
+        let words = bible.books[0].chapters[0].verses[0].words.iter().map(|w| normalize_word(&w.text)).collect_vec();
+        assert!(words.len() > 4, "{:?}", words);
+        assert_eq!(words.iter().position(|w| w == "c"), Some(words.len() - 2));
+
+        let results = search_bible(&["\"a b c d\""], &index);
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].hits[0].word_start, results[0].hits[0].word_end), (0, words.len() as u32 - 1));
+
+        assert_eq!(search_bible(&["\"b c\""], &index).len(), 1);
+        assert!(search_bible(&["\"a c\""], &index).is_empty());
+        assert_eq!(get_concordance("b c", &index).unwrap().total, 1);
+    }
+
+    fn word(word: &str) -> SearchQuery {
+        SearchQuery::Term(SearchTerm::Word(word.to_owned()))
+    }
+
+    fn parse_query(text: &str) -> Result<SearchQuery, String> {
+        SearchQuery::parse(&tokenize_search(text)?)
+    }
+
+    #[test]
+    fn test_boolean_search() {
+        assert_eq!(parse_query("light OR darkness God"), Ok(SearchQuery::Or(vec![
+            word("light"),
+            SearchQuery::And(vec![word("darkness"), word("god")]),
+        ])));
+
+        assert_eq!(parse_query("(light OR \"the deep\") AND NOT -god -waters"), Ok(SearchQuery::And(vec![
+            SearchQuery::Or(vec![word("light"), SearchQuery::Term(SearchTerm::Phrase(vec!["the".to_owned(), "deep".to_owned()]))]),
+            SearchQuery::Not(Box::new(SearchQuery::Not(Box::new(word("god"))))),
+            SearchQuery::Not(Box::new(word("waters"))),
+        ])));
+
+        // operators are only in capitals
+        assert_eq!(parse_query("light or darkness"), Ok(SearchQuery::And(vec![word("light"), word("or"), word("darkness")])));
+
+        for (text, error) in [
+            ("light OR", "`OR` must be followed by a word"),
+            ("OR light", "`OR` must be between two words"),
+            ("light AND", "`AND` must be between two words"),
+            ("(light", "Missing a closing `)`"),
+            ("light)", "Unexpected `)`, with no `(` before it"),
+            ("light ()", "Parentheses must contain a word"),
+            ("-light", "A search must have a word that is not excluded, such as `love -world`"),
+            ("light OR -darkness", "A search must have a word that is not excluded, such as `love -world`"),
+            ("light - darkness", "`-` must be followed by a word, such as `-world`"),
+            ("light NOT", "`NOT` must be followed by a word"),
+        ] {
+            assert_eq!(parse_query(text), Err(error.to_owned()), "parsing `{}`", text);
+        }
+
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+        let search = |text: &str| {
+            let words = tokenize_search(text).unwrap();
+            search_bible(&words.iter().map(|w| w.as_str()).collect_vec(), &index).iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec()
+        };
+
+        let light = scan_bible(&["light"], &bible);
+        let darkness = scan_bible(&["darkness"], &bible);
+        let god = scan_bible(&["god"], &bible);
+
+        let either = search("light OR darkness");
+        assert_eq!(either, light.iter().chain(&darkness).copied().sorted().dedup().collect_vec());
+
+        let without = search("(light OR darkness) -God");
+        assert_eq!(without, either.iter().filter(|v| !god.contains(v)).copied().collect_vec());
+        assert_eq!(search("light darkness OR light"), light);
+
+        // the uses found while matching the verses are kept for scoring them, for every term that is not excluded
+        let query = parse_query("(light OR dark*) ~waters \"the deep\" -God").unwrap();
+        let (verses, uses) = query.find_verses_and_uses(&index);
+        assert_eq!(verses, query.find_verses(&index));
+        assert!(query.get_terms().iter().all(|t| uses.contains_key(t)));
+    }
+
+    #[test]
+    fn test_stem_word() {
+        for forms in [
+            &["believe", "believed", "believeth", "believest", "believing", "believes"][..],
+            &["love", "loved", "loveth", "lovest", "lovedst", "loving"],
+            &["sin", "sinned", "sinneth", "sinnest", "sinning", "sins"],
+            &["carry", "carried", "carrieth", "carries"],
+            &["say", "saith", "said", "saying", "says"],
+            &["call", "called", "calleth", "calling"],
+            &["go", "goeth", "goest", "goes"],
+        ] {
+            let stem = stem_word(forms[0]);
+            assert!(forms.iter().all(|f| stem_word(f) == stem), "stems of {:?}: {:?}", forms, forms.iter().map(|f| stem_word(f)).collect_vec());
+        }
+
+        // words that only look like they have a suffix
+        for word in ["thing", "west", "this", "bless", "jesus", "was"] {
+            assert_eq!(stem_word(word), word);
+        }
+
+        assert_ne!(stem_word("lord"), stem_word("love"));
+    }
+
+    #[test]
+    fn test_pattern_search() {
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+        let search = |text: &str| {
+            let words = tokenize_search(text).unwrap();
+            search_bible(&words.iter().map(|w| w.as_str()).collect_vec(), &index).iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec()
+        };
+
+        let words = index.find_word("dark*").iter().map(|p| p.verse).collect_vec();
+        assert_eq!(words.len(), index.get_postings("darkness").len() + index.get_postings("dark").len());
+        assert!(index.find_word("d*ness").len() >= index.get_postings("darkness").len());
+
+        let forms = |words: &[&str]| words.iter().flat_map(|w| scan_bible(&[w], &bible)).sorted().dedup().collect_vec();
+        assert_eq!(search("~call"), forms(&["call", "called", "calleth", "calling"]));
+        assert_eq!(search("~blessing"), forms(&["bless", "blessed", "blesseth", "blessing"]));
+        assert_eq!(search("sa*eth"), forms(&["sacrificeth", "saveth"]));
+        assert_eq!(search("\"~say unto\""), search("\"said unto\" OR \"saith unto\" OR \"say unto\" OR \"sayest unto\" OR \"saying unto\""));
+        assert!(!search("\"~say unto\"").is_empty());
+
+        assert!(parse_query("~dark*").is_err());
+        assert!(parse_query("da~rk").is_err());
+        assert!(parse_query("*").is_err());
+    }
+
+    #[test]
+    fn test_scoped_search() {
+        assert_eq!(SearchScope::parse("NT"), Ok(SearchScope::Testament(Testament::New)));
+        assert_eq!(SearchScope::parse("Romans"), Ok(SearchScope::Books { start: "ROM", end: "ROM" }));
+        assert_eq!(SearchScope::parse("\"1 Cor\""), Ok(SearchScope::Books { start: "1CO", end: "1CO" }));
+        assert_eq!(SearchScope::parse("Deut-Gen"), Ok(SearchScope::Books { start: "GEN", end: "DEU" }));
+        assert!(SearchScope::parse("Hezekiah").is_err());
+
+        assert_eq!(tokenize_search("grace In:\"1 Corinthians\" -in:NT"), Ok(vec![
+            "grace".to_owned(), "In:\"1 Corinthians\"".to_owned(), "-in:NT".to_owned(),
+        ]));
+        assert!(parse_query("in:NT").is_err());
+        assert!(parse_query("God OR in:NT").is_err());
+
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+        let search = |text: &str| {
+            let words = tokenize_search(text).unwrap();
+            search_bible(&words.iter().map(|w| w.as_str()).collect_vec(), &index).iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec()
+        };
+
+        let god = scan_bible(&["god"], &bible);
+        let in_books = |ids: &[&str]| god.iter().filter(|(b, _, _)| ids.contains(&bible.books[*b as usize].id.as_str())).copied().collect_vec();
+
+        assert_eq!(search("God in:NT"), in_books(&["MAT", "MRK"]));
+        assert_eq!(search("God -in:NT"), in_books(&["GEN", "NUM", "1SA", "2SA", "PSA", "SNG", "MAL"]));
+        assert_eq!(search("God in:Num-Ps"), in_books(&["NUM", "1SA", "2SA", "PSA"]));
+        assert_eq!(search("(God in:Gen) OR (God in:Mal)"), in_books(&["GEN", "MAL"]));
+        assert!(!search("God in:Gen").is_empty());
+
+        // scoped searches are never taken as a reference
+        assert!(matches!(parse_search("God in:1Sam", &bible), ParsedSearchResult::Word { .. }));
+    }
+
+    #[test]
+    fn test_search_relevance() {
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+
+        let mut results = search_bible(&["light", "OR", "darkness"], &index);
+        let canonical = results.iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec();
+
+        // every matched word is a hit
+        for result in &results {
+            let verse = &bible.books[result.book as usize].chapters[result.chapter as usize].verses[result.verse as usize];
+            assert!(!result.hits.is_empty());
+            assert!(result.hits.iter().all(|h| ["light", "darkness"].contains(&normalize_word(&verse.words[h.word_start as usize].text).as_str())));
+            assert!(result.score > 0.0);
+        }
+
+        sort_results(&mut results, SearchOrder::Relevance);
+        assert!(results.windows(2).all(|r| r[0].score >= r[1].score));
+
+        // verses with both words are better matches than those with only one
+        assert!(results[0].hits.len() >= 2);
+
+        sort_results(&mut results, SearchOrder::Canonical);
+        assert_eq!(results.iter().map(|r| (r.book, r.chapter, r.verse)).collect_vec(), canonical);
+    }
+
+    #[test]
+    fn test_concordance() {
+        let bible = load_kjv();
+        let index = SearchIndex::new(&bible);
+
+        let concordance = get_concordance("God", &index).unwrap();
+        assert_eq!(concordance.total as usize, index.get_postings("god").len());
+        assert_eq!(concordance.verse_count as usize, scan_bible(&["god"], &bible).len());
+        assert_eq!(concordance.books.iter().map(|b| b.count).sum::<u32>(), concordance.total);
+        assert!(concordance.books.iter().all(|b| b.chapters.iter().map(|c| c.count).sum::<u32>() == b.count));
+        assert!(concordance.books.windows(2).all(|b| b[0].book < b[1].book));
+
+        let in_genesis = bible.books[0].chapters[0].verses.iter().flat_map(|v| &v.words).filter(|w| normalize_word(&w.text) == "god").count();
+        assert_eq!((concordance.books[0].book, concordance.books[0].chapters[0].chapter), (0, 0));
+        assert_eq!(concordance.books[0].chapters[0].count as usize, in_genesis);
+
+        // phrases are counted by each time the whole phrase is used, with or without quotes
+        let phrase = get_concordance("the Spirit of God", &index).unwrap();
+        assert_eq!(phrase, get_concordance("\"the spirit of god\"", &index).unwrap());
+        assert_eq!(phrase.total as usize, index.find_phrase(&["the", "spirit", "of", "god"]).len());
+        assert!(get_concordance("notaword", &index).unwrap().books.is_empty());
+        assert!(get_concordance("  ", &index).is_err());
+
+        let passage = SectionSearchResult { book: 0, chapter: 0, verse_range: Some(VerseRange { start: 0, end: 2 }) };
+        let frequencies = get_word_frequencies(&bible, &passage, 3, false);
+        assert_eq!(frequencies.len(), 3);
+        assert_eq!(frequencies[0].word, "the");
+        assert!(frequencies.windows(2).all(|f| f[0].count >= f[1].count));
+
+        let frequencies = get_word_frequencies(&bible, &passage, 100, true);
+        assert!(frequencies.iter().all(|f| !["the", "and", "of"].contains(&f.word.as_str())));
+        assert_eq!(frequencies.iter().find(|f| f.word == "god").map(|f| f.count), Some(3));
+    }
+
+    /// The book, chapter and verse range of each section, for a search that is a reference
+    fn parse_sections(text: &str, bible: &Bible) -> Vec<(u32, u32, Option<VerseRange>)> {
+        let sections = match parse_search(text, bible) {
+            ParsedSearchResult::Section { section } => vec![section],
+            ParsedSearchResult::Sections { sections } => sections,
+            other => panic!("expected `{}` to be a reference, found {:?}", text, other),
+        };
+
+        sections.iter().map(|s| (s.book, s.chapter, s.verse_range)).collect()
+    }
+
+    fn verses(start: u32, end: u32) -> Option<VerseRange> {
+        Some(VerseRange { start, end })
+    }
+
+    #[test]
+    fn test_reference_parsing() {
+        let bible = load_kjv();
+
+        assert_eq!(parse_sections("Mark 3:16-4:2", &bible), vec![(8, 2, verses(15, 34)), (8, 3, verses(0, 1))]);
+        assert_eq!(parse_sections("Mk 1:1,5,7-9; 2:1", &bible), vec![(8, 0, verses(0, 0)), (8, 0, verses(4, 4)), (8, 0, verses(6, 8)), (8, 1, verses(0, 0))]);
+        assert_eq!(parse_sections("Ps 1-3, 5", &bible), vec![(4, 0, None), (4, 1, None), (4, 2, None), (4, 4, None)]);
+        assert_eq!(parse_sections("Mark 1; 2 Sam 1:2", &bible), vec![(8, 0, None), (3, 0, verses(1, 1))]);
+
+        for text in ["2 Sam 1:3", "II Samuel 1:3", "2Sm 1.3", "ii sam. 1:3"] {
+            assert_eq!(parse_sections(text, &bible), vec![(3, 0, verses(2, 2))], "{}", text);
+        }
+
+        for text in ["Song of Solomon 1", "Song 1", "SoS 1", "Cant 1", "song of sol 1"] {
+            assert_eq!(parse_sections(text, &bible), vec![(5, 0, None)], "{}", text);
+        }
+
+        // chapters and verses that do not exist
+        assert_eq!(parse_sections("Gen 1:3-100", &bible), vec![(0, 0, verses(2, 8))]);
+        assert_eq!(parse_sections("Gen 5", &bible), vec![(0, 0, None)]);
+        assert_eq!(parse_sections("Mark 7:30-9:1", &bible), vec![(8, 6, verses(29, 36))]);
+        assert_eq!(parse_sections("Mark 1:50", &bible), vec![(8, 0, None)]);
+
+        assert!(matches!(parse_search("Hezekiah 3:1", &bible), ParsedSearchResult::Error { .. }));
+        for text in ["love light", "Mark", "light OR darkness", "God 3 darkness"] {
+            assert!(matches!(parse_search(text, &bible), ParsedSearchResult::Word { .. }), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_pattern_search_mode() {
+        let bible = load_kjv();
+
+        match parse_search(r" /\bLord of \w+s\b/ ", &bible) {
+            ParsedSearchResult::Word { words } => assert_eq!(words, vec![r"/\bLord of \w+s\b/"]),
+            other => panic!("expected a pattern search, found {:?}", other),
+        }
+
+        for text in ["/(light/", "/light/x", "//", r"/\w{100000}/"] {
+            assert!(matches!(parse_search(text, &bible), ParsedSearchResult::Error { .. }), "{}", text);
+        }
+
+        // only searches wrapped in slashes are patterns
+        assert!(parse_pattern("light/darkness").is_none());
+        assert!(parse_pattern("/light").is_none());
+
+        let search = |pattern: &str| search_pattern(&parse_pattern(pattern).unwrap().unwrap(), &bible);
+
+        let results = search(r"/\bLord of \w+s\b/");
+        assert_eq!(results.len(), 9);
+        assert_eq!((results[0].book, results[0].chapter, results[0].verse), (2, 0, 2));
+
+        let verse = &bible.books[2].chapters[0].verses[2];
+        let hit = results[0].hits[0];
+        let matched = verse.words[hit.word_start as usize..=hit.word_end as usize].iter().map(|w| normalize_word(&w.text)).collect_vec();
+        assert_eq!(matched, vec!["lord", "of", "hosts"]);
+
+        assert!(search(r"/\blord of \w+s\b/").is_empty());
+        assert_eq!(search(r"/\blord of \w+s\b/i").len(), 9);
+
+        // every word is matched, but each only once
+        let every = search("/[a-z]/");
+        assert!(every.len() <= MAX_PATTERN_RESULTS);
+        assert!(every.iter().all(|r| r.hits.windows(2).all(|h| h[0].word_start < h[1].word_start)));
+    }
+
+    #[test]
+    fn test_version_search() {
+        let kjv = load_kjv();
+        let asv = parse_bible(include_str!("../../resources/bibles/small_asv.txt")).unwrap();
+        let (kjv_index, asv_index) = (SearchIndex::new(&kjv), SearchIndex::new(&asv));
+
+        let versions = [(&kjv, &kjv_index), (&asv, &asv_index)];
+        let results = search_versions(&["light"], &kjv, &versions);
+
+        let verses = |bible: &Bible, index: &SearchIndex| search_bible(&["light"], index).iter()
+            .map(|r| (r.book, r.chapter, r.verse))
+            .filter(|(b, _, _)| kjv.get_book_index(&bible.books[*b as usize].id).is_some())
+            .collect_vec();
+
+        assert!(results.truncated.is_empty());
+        let in_version = |name: &str| results.verses.iter().filter(|r| r.versions.contains(&name.to_owned())).map(|r| (r.book, r.chapter, r.verse)).collect_vec();
+        assert_eq!(in_version(&kjv.name), verses(&kjv, &kjv_index));
+        assert_eq!(in_version(&asv.name), verses(&asv, &asv_index));
+
+        // a verse only in the ASV
+        assert!(results.verses.iter().any(|r| r.versions == vec![asv.name.clone()]));
+        assert!(results.verses.windows(2).all(|r| (r[0].book, r[0].chapter, r[0].verse) < (r[1].book, r[1].chapter, r[1].verse)));
+
+        // patterns are searched for in each version too
+        assert_eq!(search_versions(&[r"/\blight\b/"], &kjv, &versions), results);
+
+        // a version with more matching verses than `MAX_PATTERN_RESULTS` is not searched to the end, which is given in the results
+        let large = parse_bible(&format!("LARGE\n\n{}", (1..=MAX_PATTERN_RESULTS + 10).map(|v| format!("Genesis 1:{} light\n", v)).join(""))).unwrap();
+        let large_index = SearchIndex::new(&large);
+
+        let results = search_versions(&[r"/\blight\b/"], &kjv, &[(&kjv, &kjv_index), (&large, &large_index)]);
+        assert_eq!(results.truncated, vec![large.name.clone()]);
+        assert!(results.verses.iter().any(|r| r.versions == vec![kjv.name.clone(), large.name.clone()]));
+    }
+
+    #[test]
+    fn test_book_suggestions() {
+        assert_eq!(edit_distance("deutronomy", "deuteronomy"), 1);
+        assert_eq!(edit_distance("jhon", "john"), 1);
+        assert_eq!(edit_distance("phillipians", "philippians"), 2);
+        assert_eq!(edit_distance("", "job"), 3);
+
+        let bible = parse_bible("KJV\nTest\nGenesis 1:1 In the beginning\nDeuteronomy 1:1 These be the words\nJohn 1:1 In the beginning\nPhilippians 1:1 Paul\nPhilemon 1:1 Paul\n1 John 1:1 That which\n").unwrap();
+        let suggestions = |text: &str| match parse_search(text, &bible) {
+            ParsedSearchResult::Error { error, suggestions } => (error, suggestions),
+            other => panic!("expected an error for `{}`, found {:?}", text, other),
+        };
+
+        let (error, deuteronomy) = suggestions("Deutronomy 6:4");
+        assert_eq!(deuteronomy, vec!["Deuteronomy"]);
+        assert!(error.contains("Did you mean `Deuteronomy`?"), "{}", error);
+
+        assert_eq!(suggestions("Phillipians 4:13").1[0], "Philippians");
+        assert_eq!(suggestions("Jhon 3:16").1[0], "John");
+        assert_eq!(suggestions("1 Jhon 1:9").1[0], "1 John");
+
+        let (error, none) = suggestions("Hezekiah 3:1");
+        assert!(none.is_empty());
+        assert!(!error.contains("Did you mean"));
+    }
+
+    #[test]
+    fn test_search_history() {
+        let mut history = SearchHistory::default();
+        let texts = |history: &SearchHistory| history.get_searches().into_iter().map(|s| s.text).collect_vec();
+
+        history.add_recent("light");
+        history.add_recent(" John 3:16 ");
+        history.add_recent("light");
+        history.add_recent("   ");
+        assert_eq!(texts(&history), vec!["light", "John 3:16"]);
+
+        let saved = history.save("\"the Spirit of God\"", "Spirit");
+        assert_eq!(texts(&history), vec!["\"the Spirit of God\"", "light", "John 3:16"]);
+
+        // pinned searches come first, then saved ones, then the history
+        let john = history.get_searches()[2].id.clone();
+        assert!(history.set_pinned(&john, true));
+        assert_eq!(texts(&history), vec!["John 3:16", "\"the Spirit of God\"", "light"]);
+
+        assert_eq!(history.rerun(&saved).as_deref(), Some("\"the Spirit of God\""));
+        assert_eq!(history.rerun("missing"), None);
+
+        // only unpinned, unnamed searches are forgotten
+        for i in 0..MAX_RECENT_SEARCHES + 10 {
+            history.add_recent(&format!("word{}", i));
+        }
+        let searches = history.get_searches();
+        assert_eq!(searches.len(), MAX_RECENT_SEARCHES + 2);
+        assert_eq!(searches[0].id, john);
+        assert_eq!(searches[1].id, saved);
+        assert!(!texts(&history).contains(&"light".to_owned()));
+
+        history.clear_recent();
+        assert_eq!(texts(&history), vec!["John 3:16", "\"the Spirit of God\""]);
+
+        assert!(history.remove(&saved));
+        assert!(!history.remove(&saved));
+        assert_eq!(texts(&history), vec!["John 3:16"]);
+
+        let json = serde_json::to_string(&history).unwrap();
+        assert_eq!(serde_json::from_str::<SearchHistory>(&json).unwrap(), history);
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bible::Bible;
+
+use super::{pattern::{self, PATTERN_SEARCH_TIMEOUT}, search_bible, SearchIndex};
+
+// Runs the same search in several versions, to find where they differ, such as a word that is in the ASV but not the KJV.
+// The verses matched in each version are found in a base version by the book's canonical id and the versions' versifications
+// (as in `comparison`), so a verse is the same verse in every version even when it is numbered differently.
+//
+// A pattern search (see `pattern`) can stop before the end of a version, so the versions share one `PATTERN_SEARCH_TIMEOUT`,
+// and any version that was not searched to the end is given in the results, as its verses past that point are not known
+
+/// A verse of the base version, with the versions whose text of the verse matches the search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionSearchResult
+{
+    pub book: u32,
+    pub chapter: u32,
+    pub verse: u32,
+    /// In the same order as the versions were given
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionSearch
+{
+    pub verses: Vec<VersionSearchResult>,
+    /// The versions whose search stopped before the end, so verses past their last match may match them as well
+    pub truncated: Vec<String>,
+}
+
+/// Searches every version for the words of a search (as given by `parse_search`), giving the matched verses in the
+/// canonical order of `base`. Verses that do not exist in the base version are left out
+pub fn search_versions(words: &[&str], base: &Bible, versions: &[(&Bible, &SearchIndex)]) -> VersionSearch
+{
+    // a pattern is always the only word of its search
+    let pattern = match words
+    {
+        [word] => pattern::parse_pattern(word).and_then(Result::ok),
+        _ => None,
+    };
+
+    let deadline = Instant::now() + PATTERN_SEARCH_TIMEOUT;
+    let mut truncated = vec![];
+
+    let mut verses: BTreeMap<(u32, u32, u32), Vec<String>> = BTreeMap::new();
+    for (bible, index) in versions
+    {
+        let (results, stopped) = match &pattern
+        {
+            Some(pattern) => pattern::search_pattern_until(pattern, bible, deadline),
+            None => (search_bible(words, index), false),
+        };
+
+        if stopped
+        {
+            truncated.push(bible.name.clone());
+        }
+
+        for result in results
+        {
+            let id = &bible.books[result.book as usize].id;
+            let Some(book) = base.get_book_index(id) else { continue; };
+
+            let (chapter, verse) = bible.versification.map_verse(base.versification, id, result.chapter + 1, result.verse + 1);
+            let (Some(chapter), Some(verse)) = (chapter.checked_sub(1), verse.checked_sub(1)) else { continue; };
+
+            let exists = base.books[book as usize].chapters.get(chapter as usize).is_some_and(|c| (verse as usize) < c.verses.len());
+            if !exists { continue; }
+
+            let matched = verses.entry((book, chapter, verse)).or_default();
+            if !matched.contains(&bible.name)
+            {
+                matched.push(bible.name.clone());
+            }
+        }
+    }
+
+    let verses = verses.into_iter().map(|((book, chapter, verse), versions)| VersionSearchResult {
+        book,
+        chapter,
+        verse,
+        versions,
+    }).collect();
+
+    VersionSearch { verses, truncated }
+}
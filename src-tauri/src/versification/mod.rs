@@ -0,0 +1,198 @@
+mod test;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bible::*;
+
+// Translations do not all number their chapters and verses the same way. Each scheme is converted to and from the
+// KJV scheme, which is used as the standard that all other schemes are mapped through.
+//
+// Only the differences in chapter and verse numbering are modeled. Psalm titles are never counted as verses,
+// following the English convention, so the verse offsets that some editions have for titled psalms are not included
+
+/// The chapter and verse numbering scheme used by a Bible
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Versification
+{
+    /// The scheme used by the KJV and most English translations
+    #[default]
+    Kjv,
+    /// Latin Vulgate numbering, which uses the Greek numbering for the Psalms
+    Vulgate,
+    /// Septuagint numbering, which uses the Greek numbering for the Psalms, and the Hebrew chapters for Joel and Malachi
+    Lxx,
+}
+
+impl Versification
+{
+    /// Gets the scheme from an OSIS `refSystem`, such as `Bible.KJV` or `Bible.Vulg`
+    pub fn from_ref_system(ref_system: &str) -> Self
+    {
+        let ref_system = ref_system.trim().trim_start_matches("Bible.").to_ascii_lowercase();
+        if ref_system.starts_with("vulg")
+        {
+            Self::Vulgate
+        }
+        else if ref_system.starts_with("lxx") || ref_system.starts_with("septuagint")
+        {
+            Self::Lxx
+        }
+        else
+        {
+            Self::Kjv
+        }
+    }
+
+    /// Converts a 1 based chapter and verse number in this scheme, to the KJV scheme. `book_id` is the canonical id of the book
+    pub fn to_standard(&self, book_id: &str, chapter: u32, verse: u32) -> (u32, u32)
+    {
+        match (self, book_id)
+        {
+            (Self::Vulgate | Self::Lxx, "PSA") => greek_psalm_to_standard(chapter, verse),
+            (Self::Lxx, "JOL") => match chapter
+            {
+                3 => (2, verse + 27),
+                4 => (3, verse),
+                _ => (chapter, verse),
+            },
+            (Self::Lxx, "MAL") => match chapter
+            {
+                3 if verse > 18 => (4, verse - 18),
+                _ => (chapter, verse),
+            },
+            _ => (chapter, verse),
+        }
+    }
+
+    /// Converts a 1 based chapter and verse number in the KJV scheme, to this scheme
+    pub fn from_standard(&self, book_id: &str, chapter: u32, verse: u32) -> (u32, u32)
+    {
+        match (self, book_id)
+        {
+            (Self::Vulgate | Self::Lxx, "PSA") => greek_psalm_from_standard(chapter, verse),
+            (Self::Lxx, "JOL") => match chapter
+            {
+                2 if verse > 27 => (3, verse - 27),
+                3 => (4, verse),
+                _ => (chapter, verse),
+            },
+            (Self::Lxx, "MAL") => match chapter
+            {
+                4 => (3, verse + 18),
+                _ => (chapter, verse),
+            },
+            _ => (chapter, verse),
+        }
+    }
+
+    /// Converts a 1 based chapter and verse number from this scheme to `other`
+    pub fn map_verse(&self, other: Versification, book_id: &str, chapter: u32, verse: u32) -> (u32, u32)
+    {
+        if *self == other
+        {
+            return (chapter, verse);
+        }
+
+        let (chapter, verse) = self.to_standard(book_id, chapter, verse);
+        other.from_standard(book_id, chapter, verse)
+    }
+}
+
+/// Psalms 9 and 10 are joined, as are 114 and 115, while 116 and 147 are split in two
+fn greek_psalm_from_standard(chapter: u32, verse: u32) -> (u32, u32)
+{
+    match chapter
+    {
+        10 => (9, verse + 20),
+        11..=113 => (chapter - 1, verse),
+        114 => (113, verse),
+        115 => (113, verse + 8),
+        116 if verse <= 9 => (114, verse),
+        116 => (115, verse - 9),
+        117..=146 => (chapter - 1, verse),
+        147 if verse <= 11 => (146, verse),
+        147 => (147, verse - 11),
+        _ => (chapter, verse),
+    }
+}
+
+fn greek_psalm_to_standard(chapter: u32, verse: u32) -> (u32, u32)
+{
+    match chapter
+    {
+        9 if verse > 20 => (10, verse - 20),
+        10..=112 => (chapter + 1, verse),
+        113 if verse <= 8 => (114, verse),
+        113 => (115, verse - 8),
+        114 => (116, verse),
+        115 => (116, verse + 9),
+        116..=145 => (chapter + 1, verse),
+        146 => (147, verse),
+        147 => (147, verse + 11),
+        _ => (chapter, verse),
+    }
+}
+
+/// Maps a location in the `from` Bible to the same passage in the `to` Bible. Books are matched by their id, and chapters
+/// and verses by the versification of each Bible. As the words of each translation differ, a range that starts or ends
+/// on a verse boundary keeps doing so, and any other word positions are kept where they are (limited to the length of the verse).
+/// Returns `None` if the passage does not exist in the `to` Bible
+pub fn map_location(location: &ReferenceLocation, from: &Bible, to: &Bible) -> Option<ReferenceLocation>
+{
+    let from_book = from.books.get(location.chapter.book as usize)?;
+    let from_chapter = from_book.chapters.get(location.chapter.number as usize)?;
+
+    let book_index = to.get_book_index(&from_book.id)?;
+    let to_book = &to.books[book_index as usize];
+
+    let map = |verse: u32| {
+        let (chapter, verse) = from.versification.map_verse(to.versification, &from_book.id, location.chapter.number + 1, verse + 1);
+        (chapter.checked_sub(1), verse.checked_sub(1))
+    };
+
+    let (Some(chapter_number), Some(verse_start)) = map(location.range.verse_start) else {
+        return None;
+    };
+
+    let to_chapter = to_book.chapters.get(chapter_number as usize)?;
+    let last_verse = to_chapter.verses.len().checked_sub(1)? as u32;
+    if verse_start > last_verse
+    {
+        return None;
+    }
+
+    // a location can only be in one chapter, so a range split across two chapters is cut off at the end of the first
+    let (verse_end, is_cut_off) = match map(location.range.verse_end)
+    {
+        (Some(c), Some(v)) if c == chapter_number && v <= last_verse => (v, false),
+        _ => (last_verse, true),
+    };
+
+    let from_end_len = from_chapter.verses.get(location.range.verse_end as usize).map_or(0, |v| v.words.len() as u32);
+    let start_len = to_chapter.verses[verse_start as usize].words.len() as u32;
+    let end_len = to_chapter.verses[verse_end as usize].words.len() as u32;
+
+    let word_start = location.range.word_start.min(start_len.saturating_sub(1));
+    let word_end = if is_cut_off || location.range.word_end + 1 >= from_end_len
+    {
+        end_len.saturating_sub(1)
+    }
+    else
+    {
+        location.range.word_end.min(end_len.saturating_sub(1))
+    };
+
+    Some(ReferenceLocation {
+        chapter: ChapterIndex {
+            book: book_index,
+            number: chapter_number,
+        },
+        range: WordRange {
+            verse_start,
+            word_start,
+            verse_end,
+            word_end: if verse_start == verse_end { word_end.max(word_start) } else { word_end },
+        },
+    })
+}
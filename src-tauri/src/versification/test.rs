@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests
+{
+    use crate::bible::{Bible, Book, Chapter, ChapterIndex, ReferenceLocation, Testament, Verse, Word, WordRange};
+    use crate::versification::{map_location, Versification};
+
+    fn create_verse(text: &str) -> Verse
+    {
+        Verse {
+            words: text.split(' ').map(|w| Word { text: w.to_owned(), italicized: false, red: false, strongs: vec![], morph: None }).collect(),
+        }
+    }
+
+    /// A Bible with Joel, where each verse is numbered `chapter:verse`, following the given scheme
+    fn create_joel_bible(name: &str, versification: Versification) -> Bible
+    {
+        let chapter_lengths: &[u32] = match versification
+        {
+            Versification::Lxx => &[20, 27, 5, 21],
+            _ => &[20, 32, 21],
+        };
+
+        let chapters = chapter_lengths.iter().enumerate().map(|(c, len)| Chapter {
+            verses: (1..=*len).map(|v| create_verse(&format!("verse {}:{} of Joel", c + 1, v))).collect(),
+        }).collect();
+
+        Bible {
+            name: name.to_owned(),
+            desc: String::new(),
+            books: vec![Book { name: "Joel".to_owned(), id: "JOL".to_owned(), testament: Testament::Old, chapters }],
+            versification,
+        }
+    }
+
+    fn location(chapter: u32, verse_start: u32, word_start: u32, verse_end: u32, word_end: u32) -> ReferenceLocation
+    {
+        ReferenceLocation {
+            chapter: ChapterIndex { book: 0, number: chapter },
+            range: WordRange { verse_start, word_start, verse_end, word_end },
+        }
+    }
+
+    #[test]
+    fn test_psalm_numbering()
+    {
+        let greek = Versification::Vulgate;
+        assert_eq!(greek.from_standard("PSA", 23, 1), (22, 1));
+        assert_eq!(greek.from_standard("PSA", 10, 1), (9, 21));
+        assert_eq!(greek.from_standard("PSA", 116, 10), (115, 1));
+        assert_eq!(greek.from_standard("PSA", 147, 12), (147, 1));
+
+        for (chapter, verse) in [(1, 1), (9, 20), (10, 18), (51, 3), (114, 8), (115, 1), (116, 9), (116, 19), (147, 11), (147, 20), (150, 6)]
+        {
+            let mapped = greek.from_standard("PSA", chapter, verse);
+            assert_eq!(greek.to_standard("PSA", mapped.0, mapped.1), (chapter, verse));
+        }
+
+        // books without any differences are left alone
+        assert_eq!(greek.map_verse(Versification::Kjv, "GEN", 3, 15), (3, 15));
+    }
+
+    #[test]
+    fn test_hebrew_chapter_divisions()
+    {
+        let lxx = Versification::Lxx;
+        assert_eq!(Versification::Kjv.map_verse(lxx, "JOL", 2, 28), (3, 1));
+        assert_eq!(Versification::Kjv.map_verse(lxx, "JOL", 3, 4), (4, 4));
+        assert_eq!(Versification::Kjv.map_verse(lxx, "MAL", 4, 6), (3, 24));
+        assert_eq!(lxx.map_verse(Versification::Kjv, "MAL", 3, 19), (4, 1));
+        assert_eq!(lxx.map_verse(Versification::Vulgate, "JOL", 3, 5), (2, 32));
+    }
+
+    #[test]
+    fn test_from_ref_system()
+    {
+        assert_eq!(Versification::from_ref_system("Bible.KJV"), Versification::Kjv);
+        assert_eq!(Versification::from_ref_system("Bible.Vulg"), Versification::Vulgate);
+        assert_eq!(Versification::from_ref_system("Bible.LXX"), Versification::Lxx);
+        assert_eq!(Versification::from_ref_system("Bible.NRSV"), Versification::Kjv);
+    }
+
+    #[test]
+    fn test_map_location()
+    {
+        let kjv = create_joel_bible("KJV", Versification::Kjv);
+        let lxx = create_joel_bible("LXX", Versification::Lxx);
+
+        // Joel 2:28 (whole verse) is Joel 3:1 in the LXX
+        let mapped = map_location(&location(1, 27, 0, 27, 3), &kjv, &lxx).unwrap();
+        assert_eq!(mapped, location(2, 0, 0, 0, 3));
+        assert_eq!(lxx.books[0].chapters[2].verses[0].words[1].text, "3:1");
+
+        // and back again
+        assert_eq!(map_location(&mapped, &lxx, &kjv), Some(location(1, 27, 0, 27, 3)));
+
+        // Joel 2:27-28 crosses into the next LXX chapter, so is cut off at the end of chapter 2
+        let mapped = map_location(&location(1, 26, 1, 27, 2), &kjv, &lxx).unwrap();
+        assert_eq!(mapped, location(1, 26, 1, 26, 3));
+
+        // the same versification only maps books
+        assert_eq!(map_location(&location(0, 3, 1, 4, 2), &kjv, &kjv), Some(location(0, 3, 1, 4, 2)));
+
+        // Joel 4:22 in the LXX would be 3:22, which the KJV does not have
+        assert_eq!(map_location(&location(3, 21, 0, 21, 0), &lxx, &kjv), None);
+    }
+}
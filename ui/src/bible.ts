@@ -1,6 +1,6 @@
 import { invoke, debug_print, color_to_hex, trim_string, capitalize_first_char } from "./utils/index.js";
 import { push_section, get_current_view_state } from "./view_states.js";
-import { BibleSection, BookView, ChapterIndex, ChapterView, Verse, VerseRange } from "./bindings.js";
+import { BibleSection, BookView, ChapterComparison, ChapterDiff, ChapterIndex, ChapterView, Concordance, Verse, VerseRange, VersionSearch, WordFrequency, WordStrongs } from "./bindings.js";
 import { EventHandler, Listener } from "./utils/events.js";
 import * as utils from "./utils/index.js";
 
@@ -148,6 +148,67 @@ export async function get_bible_versions(): Promise<string[]>
     return await invoke('get_bible_versions', {});
 }
 
+/**
+ * Imports the Bible file at `path` into the app data folder. Returns the name of the new version, or null if the import failed
+ */
+export async function import_bible(path: string): Promise<string | null>
+{
+    return await invoke('import_bible', { path: path });
+}
+
+/**
+ * Gets the chapter (or the verses in `verse_range`) of the current version, aligned verse by verse with each of the `versions`.
+ * Versions that are not installed are left out. Returns null if the chapter does not exist
+ */
+export async function get_chapter_comparison(chapter: ChapterIndex, verse_range: VerseRange | null, versions: string[]): Promise<ChapterComparison | null>
+{
+    return await invoke('get_chapter_comparison', { chapter: chapter, verse_range: verse_range, versions: versions });
+}
+
+/**
+ * Diffs the words of a chapter (or the verses in `verse_range`) between two versions. The chapter is in the numbering of the `from` version.
+ * Returns null if either version is not installed, or the chapter does not exist
+ */
+export async function get_chapter_diff(chapter: ChapterIndex, verse_range: VerseRange | null, from: string, to: string): Promise<ChapterDiff | null>
+{
+    return await invoke('get_chapter_diff', { chapter: chapter, verse_range: verse_range, from: from, to: to });
+}
+
+/**
+ * Gets the Strong's numbers and morphology of a word in the current version. Returns null if the word does not exist
+ */
+export async function get_word_strongs(chapter: ChapterIndex, verse: number, word: number): Promise<WordStrongs | null>
+{
+    return await invoke('get_word_strongs', { chapter: chapter, verse: verse, word: word });
+}
+
+/**
+ * Runs a word search (as given by `parse_bible_search`) in every installed version, giving the versions that matched each verse.
+ * Verses are in the numbering of the current version. A pattern search can stop before the end of a version, and those versions are
+ * given in `truncated`
+ */
+export async function search_versions(words: string[]): Promise<VersionSearch>
+{
+    return await invoke('run_version_search', { words: words });
+}
+
+/**
+ * Counts the uses of a word or phrase in each book and chapter of a version, or the current version if `version` is null.
+ * Returns null if the version is not installed, or the text has no words
+ */
+export async function get_concordance(text: string, version: string | null = null): Promise<Concordance | null>
+{
+    return await invoke('get_word_concordance', { text: text, version: version });
+}
+
+/**
+ * Gets the `count` most used words in a passage, from most to least used. Common words such as `the` are left out if `ignore_common`
+ */
+export async function get_word_frequencies(passage: BibleSection, count: number, ignore_common: boolean, version: string | null = null): Promise<WordFrequency[] | null>
+{
+    return await invoke('get_passage_word_frequencies', { passage: passage, count: count, ignore_common: ignore_common, version: version });
+}
+
 export async function get_book_name(book_index: number): Promise<string>
 {
     return await invoke('get_book_name', { book: book_index });
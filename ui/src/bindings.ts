@@ -1,5 +1,6 @@
 // ---------------------- Bible Bindings ----------------------
-export type Word = { text: string, italicized: boolean, red: boolean };
+export type Word = { text: string, italicized: boolean, red: boolean, strongs?: string[], morph?: string };
+export type WordStrongs = { text: string, strongs: string[], morph: string | null };
 
 export type Verse = { words: Array<Word> };
 export type Chapter = { verses: Array<Verse> };
@@ -10,9 +11,19 @@ export type WordRange = { verse_start: number, word_start: number, verse_end: nu
 export type ReferenceLocation = { chapter: ChapterIndex, range: WordRange };
 export type VersePosition = { book: number, chapter: number, verse: number};
 
-export type BookView = { name: string, chapter_count: number };
+export type Testament = 'old' | 'new';
+export type BookView = { name: string, id: string, testament: Testament, chapter_count: number };
 export type ChapterView = { verses: Array<number> };
 
+export type ComparedVerse = { chapter: ChapterIndex, verse: number, text: Verse };
+export type VerseComparison = { verse: number, verses: Array<ComparedVerse | null> };
+export type ChapterComparison = { versions: string[], verses: Array<VerseComparison> };
+
+export type DiffKind = 'equal' | 'insert' | 'delete';
+export type DiffSpan = { kind: DiffKind, word_start: number, words: Array<Word> };
+export type VerseDiff = { verse: number, to_verse: ComparedVerse | null, spans: Array<DiffSpan> };
+export type ChapterDiff = { from: string, to: string, verses: Array<VerseDiff> };
+
 // ---------------------- Note Bindings -----------------------
 export type HighlightCategories = { [key: string]: HighlightCategory }
 export type HighlightCategory = { color: Color, name: string, description: string, priority: number, source_type: NoteSourceType, id: string };
@@ -20,9 +31,20 @@ export type NoteSourceType = 'html' | 'json' | 'markdown';
 export type NoteData = { id: string, text: string, locations: Array<ReferenceLocation>, source_type: NoteSourceType };
 export type WordAnnotations = { highlights: Array<string>, notes: Array<string> };
 export type ChapterAnnotations = { [key: number]: WordAnnotations | undefined };
+export type NotebookQuery = { text: string | null, category: string | null, passage: BibleSection | null };
+export type NoteSearchResult = { id: string, locations: Array<ReferenceLocation>, categories: string[] };
 
 // ------------------------ Misc Bindings ----------------------------
 export type Color = { r: number, g: number, b: number };
 export type BibleSection = { book: number, chapter: number, verse_range: VerseRange | null };
 export type SearchSection = { words: string[], display_index: number, editing_note_location: ReferenceLocation | null };
+export type SearchOrder = 'canonical' | 'relevance';
+export type SavedSearch = { id: string, text: string, name: string | null, pinned: boolean, last_run: number };
+export type VersionSearchResult = { book: number, chapter: number, verse: number, versions: string[] };
+export type VersionSearch = { verses: Array<VersionSearchResult>, truncated: string[] };
+export type Concordance = { total: number, verse_count: number, books: Array<BookOccurrences> };
+export type BookOccurrences = { book: number, count: number, chapters: Array<ChapterOccurrences> };
+export type ChapterOccurrences = { chapter: number, count: number };
+export type WordFrequency = { word: string, count: number };
+export type LexiconEntry = { number: string, lemma: string, transliteration: string, pronunciation: string | null, derivation: string, definition: string, gloss: string };
 export type AppSettings = { ui_scale: number, volume: number, font: string | null };
\ No newline at end of file
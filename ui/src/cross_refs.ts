@@ -0,0 +1,10 @@
+import { BibleSection, ChapterIndex } from "./bindings.js";
+import { invoke } from "./utils/index.js";
+
+/**
+ * Gets the passages related to a verse in the current version, most related first. `verse` is 0 based
+ */
+export async function get_cross_references(chapter: ChapterIndex, verse: number): Promise<BibleSection[]>
+{
+    return await invoke('get_cross_references', { chapter: chapter, verse: verse });
+}
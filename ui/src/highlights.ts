@@ -30,6 +30,11 @@ export async function get_categories(): Promise<HighlightCategories>
     return JSON.parse(await utils.invoke('get_highlight_categories', {}));
 }
 
+export async function search_categories(text: string): Promise<HighlightCategory[]>
+{
+    return await utils.invoke('search_highlight_categories', { text: text });
+}
+
 export async function get_sorted_categories(): Promise<HighlightCategory[]>
 {
     return get_categories().then(cats => {
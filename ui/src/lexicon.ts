@@ -0,0 +1,18 @@
+import { LexiconEntry } from "./bindings.js";
+import { invoke } from "./utils/index.js";
+
+/**
+ * Gets the Strong's dictionary entry for a number, such as `H7225` or `G26`. Returns null if there is no entry
+ */
+export async function get_lexicon_entry(number: string): Promise<LexiconEntry | null>
+{
+    return await invoke('get_lexicon_entry', { number: number });
+}
+
+/**
+ * Finds the Strong's dictionary entries that have all the words of `gloss` in their English gloss or definition
+ */
+export async function search_lexicon(gloss: string): Promise<LexiconEntry[]>
+{
+    return await invoke('search_lexicon', { gloss: gloss });
+}
@@ -1,4 +1,4 @@
-import { NoteData, NoteSourceType, ReferenceLocation } from "./bindings.js";
+import { NoteData, NoteSearchResult, NotebookQuery, NoteSourceType, ReferenceLocation } from "./bindings.js";
 import * as utils from "./utils/index.js";
 import * as bible from "./bible.js";
 
@@ -40,6 +40,14 @@ export async function create_note(location: ReferenceLocation): Promise<string>
     return await utils.invoke('add_note', { text: '', locations: [location], source_type: source_type});
 }
 
+/**
+ * Finds the notes of the current notebook with all the words of `text`, highlighted with `category`, or over `passage`
+ */
+export async function search_notebook(query: NotebookQuery): Promise<NoteSearchResult[]>
+{
+    return await utils.invoke('search_notebook', { query: query });
+}
+
 export async function get_editing_note(): Promise<string | null>
 {
     return await utils.invoke('get_editing_note', {}) as string | null
@@ -0,0 +1,85 @@
+import * as utils from "../utils/index.js";
+import * as bible from "../bible.js";
+import * as pages from "./pages.js";
+import * as view_states from "../view_states.js";
+import { render_word } from "../rendering/bible_rendering.js";
+import { BibleSection, ComparedVerse } from "../bindings.js";
+
+const CONTENT_ID: string = "comparison-content";
+const CHAPTER_NAME_ID: string = "chapter-name";
+
+export type ComparePageData = { section: BibleSection, versions: string[] };
+
+export async function run()
+{
+    let data = utils.decode_from_url(window.location.href) as ComparePageData;
+    utils.init_format_copy_event_listener();
+
+    bible.add_version_changed_listener(_ => {
+        utils.scrolling.save_scroll(null);
+    });
+
+    await pages.invoke_shared_main_page_initializers(() => view_states.goto_current_view_state());
+    await pages.init_header();
+
+    display_comparison(data).then(_ => {
+        document.body.style.visibility = 'visible';
+        utils.scrolling.load_scroll();
+    });
+}
+
+export async function display_comparison(data: ComparePageData): Promise<void>
+{
+    const content = document.getElementById(CONTENT_ID);
+    if(content === null) return;
+    content.replaceChildren();
+
+    let chapter = { book: data.section.book, number: data.section.chapter };
+    let current_version = await bible.get_current_bible_version();
+
+    // the current version is always shown first, so it is compared with itself
+    let versions = [current_version, ...data.versions.filter(v => v !== current_version)];
+    let comparison = await bible.get_chapter_comparison(chapter, data.section.verse_range, versions);
+    if(comparison === null) return;
+
+    let name = (await bible.get_bible_view())[chapter.book].name;
+    utils.set_html(CHAPTER_NAME_ID, `${name} ${chapter.number + 1}`);
+
+    let header = document.createElement('div');
+    header.classList.add('comparison-row', 'comparison-header');
+    comparison.versions.forEach(v => {
+        let version = document.createElement('div');
+        version.innerHTML = v;
+        header.appendChild(version);
+    });
+    content.appendChild(header);
+
+    comparison.verses.forEach(v => {
+        let row = document.createElement('div');
+        row.classList.add('comparison-row');
+        v.verses.forEach(verse => row.appendChild(render_compared_verse(verse)));
+        content.appendChild(row);
+    });
+}
+
+function render_compared_verse(verse: ComparedVerse | null): HTMLElement
+{
+    let node = document.createElement('div');
+    node.classList.add('comparison-verse');
+    if(verse === null)
+    {
+        node.classList.add('missing');
+        return node;
+    }
+
+    let number = document.createElement('span');
+    number.classList.add('verse-number');
+    number.innerHTML = `${verse.chapter.number + 1}:${verse.verse + 1}`;
+    node.appendChild(number);
+
+    verse.text.words.forEach(word => {
+        node.appendChild(render_word({ word, has_note: false, searched: null, color: null }));
+    });
+
+    return node;
+}
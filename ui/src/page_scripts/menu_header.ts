@@ -5,6 +5,7 @@ import * as view_states from "../view_states.js";
 import { show_error_popup } from "../popups/error_popup.js";
 import { ChapterIndex } from "../bindings.js";
 import * as sync from "../cloud_sync.js";
+import * as search_history from "../search_history.js";
 
 export function get_header(): HTMLElement
 {
@@ -23,6 +24,7 @@ export async function init_main_page_header(args: {
 {
     let header = get_header();
     header.appendChild(await spawn_version_dropdown());
+    header.appendChild(await spawn_compare_button());
 
     let on_chapter_select = (chapter: ChapterIndex): void => {
         view_states.push_section({
@@ -89,7 +91,7 @@ function spawn_searchbar(): SearchBarData
         i.type = 'text';
 
         let view_state = await view_states.get_current_view_state();
-        if (view_state.type === 'chapter')
+        if (view_state.type === 'chapter' || view_state.type === 'compare')
         {
             let book_name = await bible.get_book_name(view_state.chapter.book);
             let chapter = view_state.chapter.number + 1;
@@ -134,7 +136,11 @@ function spawn_searchbar(): SearchBarData
         button.button.addEventListener('click', e => {
             let value = input.value;
             utils.invoke('parse_bible_search', { text: value }).then(result => {
-                if(result.type !== 'error') { utils.reset_scroll() }
+                if(result.type !== 'error')
+                {
+                    utils.reset_scroll();
+                    search_history.add_search_to_history(value);
+                }
     
                 if(result.type === 'error')
                 {
@@ -152,6 +158,12 @@ function spawn_searchbar(): SearchBarData
                         view_states.goto_current_view_state();
                     });
                 }
+                else if (result.type === 'sections')
+                {
+                    view_states.push_sections(result.sections).then(() => {
+                        view_states.goto_current_view_state();
+                    });
+                }
                 else 
                 {
                     show_error_popup(SEARCH_ERROR_ID, true, `Search type ${result.type} unsupported on the front end`);
@@ -250,6 +262,40 @@ export async function spawn_version_dropdown(): Promise<HTMLElement>
     return dropdown.root;
 }
 
+/**
+ * Compares the chapter being read in every installed version. Only active when reading a chapter
+ */
+export async function spawn_compare_button(): Promise<HTMLElement>
+{
+    const view_state = await view_states.get_current_view_state();
+
+    let button = utils.spawn_image_button_args({
+        image: utils.images.BOOKS,
+        title: 'Compare versions',
+        on_click: async _ => {
+            if (view_state.type !== 'chapter') return;
+
+            let versions = await bible.get_bible_versions();
+            let section = {
+                book: view_state.chapter.book,
+                chapter: view_state.chapter.number,
+                verse_range: view_state.verse_range,
+            };
+
+            view_states.push_comparison(section, versions).then(_ => {
+                view_states.goto_current_view_state();
+            });
+        }
+    });
+
+    if (view_state.type !== 'chapter')
+    {
+        button.button.classList.add('inactive');
+    }
+
+    return button.button;
+}
+
 type SettingsDropdownType = 'settings' | 'readings' | 'help' | 'highlights';
 function spawn_settings_dropdown(old_path: string, shift: boolean): HTMLElement
 {
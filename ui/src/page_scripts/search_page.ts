@@ -1,6 +1,6 @@
 import * as utils from "../utils/index.js";
 import * as pages from "./pages.js";
-import { ReferenceLocation, SearchSection } from "../bindings.js";
+import { ReferenceLocation, SearchOrder, SearchSection } from "../bindings.js";
 import * as search from "../rendering/word_search.js";
 import { PanelData } from "../popups/side_popup.js";
 import * as bible from "../bible.js";
@@ -27,7 +27,7 @@ export async function run()
     })
 }
 
-export async function display_search(section: SearchSection, header_data: MainPageHeaderData): Promise<void>
+export async function display_search(section: SearchSection, header_data: MainPageHeaderData, order: SearchOrder = 'canonical'): Promise<void>
 {
     const word_popup = document.getElementById('word-popup');
     const side_popup = document.getElementById('popup-panel');
@@ -44,7 +44,7 @@ export async function display_search(section: SearchSection, header_data: MainPa
         }
     }
     
-    let search_result = await utils.invoke('run_word_search', { words: section.words });
+    let search_result = await utils.invoke('run_word_search', { words: section.words, order: order });
 
     await search.render_search_result({
             result: search_result, 
@@ -56,6 +56,10 @@ export async function display_search(section: SearchSection, header_data: MainPa
             on_rendered: () => {
                 header_data.update_nav_active();;
             }, 
-            on_search: header_data.on_search
+            on_search: header_data.on_search,
+            order: order,
+            on_order_changed: new_order => {
+                display_search({ ...section, display_index: 0 }, header_data, new_order);
+            },
         });
 }
\ No newline at end of file
@@ -2,11 +2,14 @@ import { Color, Word } from "../bindings.js";
 import * as utils from "../utils/index.js";
 
 export const HIGHLIGHT_SELECTED_WORD_COLOR = 'blueviolet';
+export const RED_LETTER_COLOR = '#b30000';
 
 export type WordData = {
     word: Word,
     has_note: boolean,
     searched: string[] | null,
+    /** Bolds the word, as when it is a known search hit */
+    bolded?: boolean,
     color: Color | null,
 }
 
@@ -24,8 +27,13 @@ export function render_word(data: WordData)
     {
         word_node.style.fontStyle = 'italic';
     }
+
+    if (data.word.red)
+    {
+        word_node.style.color = RED_LETTER_COLOR;
+    }
     
-    if (data.searched !== null && data.searched.includes(utils.trim_string(data.word.text).toLocaleLowerCase()))
+    if (data.bolded || (data.searched !== null && data.searched.includes(utils.trim_string(data.word.text).toLocaleLowerCase())))
     {
         word_node = bold(word_node);
     }
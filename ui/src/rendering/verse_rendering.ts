@@ -14,6 +14,8 @@ export type VerseRenderArgs = {
     word_popup: HTMLElement,
     side_popup_data: sp.PanelData | null,
     bolded: string[] | null,
+    /** The indices of words in the verse to bold */
+    bolded_words?: number[],
     on_search: (msg: string) => void
 }
 
@@ -90,6 +92,7 @@ export async function render_verse(args: VerseRenderArgs): Promise<HTMLElement[]
         let word_node = rendering.render_word({
             word: words[i], 
             searched: args.bolded, 
+            bolded: args.bolded_words?.includes(i),
             has_note: has_notes,
             color: color,
         });
@@ -1,7 +1,7 @@
 import * as utils from "../utils/index.js";
 import * as bible from "../bible.js";
 import { push_word_search } from "../view_states.js";
-import { ReferenceLocation, VersePosition } from "../bindings.js";
+import { ReferenceLocation, SearchOrder, WordRange } from "../bindings.js";
 import * as verse_renderer from "./verse_rendering.js";
 import { PanelData } from "../popups/side_popup.js";
 import * as selection from "../selection.js";
@@ -13,6 +13,10 @@ export type WordSearchResult = {
     book: number,
     chapter: number,
     verse: number,
+    /** The words matched by the search, where a phrase is a single range */
+    hits: WordRange[],
+    /** How well the verse matches the search, higher is better */
+    score: number,
 }
 
 /**
@@ -31,6 +35,8 @@ export async function render_search_result(args: {
     on_rendered: () => void; 
     on_search: (msg: string) => void; 
     editing_note_location: ReferenceLocation | null,
+    order: SearchOrder,
+    on_order_changed: (order: SearchOrder) => void,
 }): Promise<void>
 {
     let { result, searched, results_id, word_popup, side_popup_data, display_index, on_rendered, on_search } = args;
@@ -54,6 +60,10 @@ export async function render_search_result(args: {
     let new_children: HTMLElement[] = [];
     
     append_search_header(result_count, new_children, searched);
+    if (result_count > 1)
+    {
+        new_children.push(spawn_order_select(args.order, args.on_order_changed));
+    }
     
     let start = display_index * MAX_DISPLAY;
     let end = Math.min(result_count, MAX_DISPLAY + start);
@@ -62,9 +72,9 @@ export async function render_search_result(args: {
 
     for(let i = start; i < end; i++)
     {
-        let result_data = result[i] as VersePosition;
+        let result_data = result[i];
 
-        let verse_node = await spawn_verse(result_data, searched, word_popup,  side_popup_data, on_search);
+        let verse_node = await spawn_verse(result_data, word_popup,  side_popup_data, on_search);
 
         let word_offset = await bible.get_verse_word_offset(result_data.book, result_data.chapter, result_data.verse);
         selection.push_selection_range(verse_node, {book: result_data.book, number: result_data.chapter}, word_offset);
@@ -194,17 +204,20 @@ async function generate_section_buttons(search_results: any[], render_section: (
     return parent;
 }
 
-async function spawn_verse(position: VersePosition, searched: string[], word_popup: HTMLElement, side_popup_data: PanelData | null, on_search: (msg: string) => void)
+async function spawn_verse(result: WordSearchResult, word_popup: HTMLElement, side_popup_data: PanelData | null, on_search: (msg: string) => void)
 {
-    searched = searched.map(s => s.toLocaleLowerCase());
     let verse_node = document.createElement('p');
+
+    // the hits are already in this verse, but may be in several ranges for phrases
+    let bolded_words = result.hits.flatMap(h => Array.from({ length: h.word_end - h.word_start + 1 }, (_, i) => h.word_start + i));
     
     let elements = await verse_renderer.render_verse({
-        chapter: { book: position.book, number: position.chapter},
-        verse: position.verse,
+        chapter: { book: result.book, number: result.chapter},
+        verse: result.verse,
         word_popup: word_popup,
         side_popup_data: side_popup_data,
-        bolded: searched,
+        bolded: null,
+        bolded_words: bolded_words,
         on_search: on_search
     });
 
@@ -212,6 +225,28 @@ async function spawn_verse(position: VersePosition, searched: string[], word_pop
     return verse_node;
 }
 
+function spawn_order_select(order: SearchOrder, on_order_changed: (order: SearchOrder) => void): HTMLElement
+{
+    let select = document.createElement('select');
+    select.classList.add('search-order');
+    select.title = 'Sort the results';
+
+    const options: [SearchOrder, string][] = [['canonical', 'Bible order'], ['relevance', 'Best matches']];
+    options.forEach(([value, name]) => {
+        let option = document.createElement('option');
+        option.value = value;
+        option.innerHTML = name;
+        option.selected = value === order;
+        select.appendChild(option);
+    });
+
+    select.addEventListener('change', _ => {
+        on_order_changed(select.value as SearchOrder);
+    });
+
+    return select;
+}
+
 export async function format_reference_id(book: number, chapter: number, verse: number): Promise<string>
 {
     let book_title = await bible.get_book_name(book);
@@ -0,0 +1,50 @@
+import { SavedSearch } from "./bindings.js";
+import { invoke } from "./utils/index.js";
+
+/**
+ * Gets the pinned, saved and recent searches, in that order
+ */
+export async function get_search_history(): Promise<SavedSearch[]>
+{
+    return await invoke('get_search_history', {});
+}
+
+export async function add_search_to_history(text: string): Promise<void>
+{
+    return await invoke('add_search_to_history', { text: text });
+}
+
+/**
+ * Saves a search with a name, returning its id
+ */
+export async function save_search(text: string, name: string): Promise<string>
+{
+    return await invoke('save_search', { text: text, name: name });
+}
+
+/**
+ * Parses a saved or recent search again, in the current version, giving the same result as `parse_bible_search`.
+ * Returns null if there is no such search
+ */
+export async function rerun_search(id: string): Promise<any | null>
+{
+    return await invoke('rerun_search', { id: id });
+}
+
+export async function set_search_pinned(id: string, pinned: boolean): Promise<boolean>
+{
+    return await invoke('set_search_pinned', { id: id, pinned: pinned });
+}
+
+export async function delete_search(id: string): Promise<boolean>
+{
+    return await invoke('delete_search', { id: id });
+}
+
+/**
+ * Forgets every recent search that is not pinned or saved
+ */
+export async function clear_search_history(): Promise<void>
+{
+    return await invoke('clear_search_history', {});
+}
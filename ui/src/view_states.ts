@@ -3,6 +3,8 @@ import * as utils from "./utils/index.js";
 import * as notes from "./notes.js";
 import { BibleNotePageData } from "./page_scripts/bible_note_page.js";
 import { SearchNotePageData } from "./page_scripts/search_note_page.js";
+import { ComparePageData } from "./page_scripts/compare_page.js";
+import { spawn_alert_popup_basic } from "./popups/alert_select_popup.js";
 
 export async function is_last_view_state(): Promise<boolean>
 {
@@ -30,6 +32,45 @@ export async function push_section(section: BibleSection): Promise<void>
     }});
 }
 
+/**
+ * Goes to the first section of a search for several references, as only one passage can be shown at a time. Sections right
+ * after it in the same chapter are shown with it, so `Rom 1:1-3,4-6` shows verses 1 through 6, but `Rom 1:1,5` only shows verse 1,
+ * after telling the user that the rest are not shown
+ */
+export async function push_sections(sections: BibleSection[]): Promise<void>
+{
+    let first = sections[0];
+    let verse_range = first.verse_range;
+
+    let shown = 1;
+    for (let section of sections.slice(1))
+    {
+        let next = section.verse_range;
+        if (section.book !== first.book || section.chapter !== first.chapter || verse_range === null || next === null)
+        {
+            break;
+        }
+
+        if (next.start < verse_range.start || next.start > verse_range.end + 1)
+        {
+            break;
+        }
+
+        verse_range = { start: verse_range.start, end: Math.max(verse_range.end, next.end) };
+        shown++;
+    }
+
+    if (shown < sections.length)
+    {
+        await new Promise<void>(resolve => {
+            let message = `The search has ${sections.length} passages, but only one passage can be shown at a time, so only the first is shown.`;
+            spawn_alert_popup_basic('Several Passages', message, resolve);
+        });
+    }
+
+    return await push_section({ book: first.book, chapter: first.chapter, verse_range: verse_range });
+}
+
 export async function push_word_search(words: string[], display_index: number, note_editing_location?: ReferenceLocation): Promise<void>
 {
     return await utils.invoke('push_view_state', { view_state: {
@@ -41,6 +82,20 @@ export async function push_word_search(words: string[], display_index: number, n
     } as ViewState});
 }
 
+export async function push_comparison(section: BibleSection, versions: string[]): Promise<void>
+{
+    return await utils.invoke('push_view_state', { view_state: {
+        type: 'compare',
+        chapter: {
+            book: section.book,
+            number: section.chapter
+        },
+        verse_range: section.verse_range,
+        versions: versions,
+        scroll: 0.0
+    } as ViewState});
+}
+
 export async function push_highlights()
 {
     return await utils.invoke('push_view_state', { view_state: {
@@ -103,12 +158,27 @@ export async function goto_current_view_state()
             window.location.href = url;
         }
     }
+    else if(current.type === 'compare')
+    {
+        let data: ComparePageData = {
+            section: {
+                book: current.chapter.book,
+                chapter: current.chapter.number,
+                verse_range: current.verse_range
+            },
+            versions: current.versions,
+        };
+
+        let url = utils.encode_to_url(base_path + 'compare_page.html', data);
+        window.location.href = url;
+    }
 }
 
 export enum ViewStateType
 {
     Chapter,
     Search,
+    Compare,
 }
 
 export async function get_view_state_type(): Promise<ViewStateType | null>
@@ -122,6 +192,10 @@ export async function get_view_state_type(): Promise<ViewStateType | null>
     {
         return ViewStateType.Search;
     }
+    else if (current.type == 'compare')
+    {
+        return ViewStateType.Compare;
+    }
     else 
     {
         return null;
@@ -141,6 +215,13 @@ export type ViewState =
       display_index: number;
       scroll: number;
       note_editing_location: ReferenceLocation | null,
+    }
+  | {
+      type: "compare";
+      chapter: ChapterIndex;
+      verse_range: VerseRange | null;
+      versions: string[];
+      scroll: number;
     };
 
 export async function get_current_view_state(): Promise<ViewState>
@@ -179,6 +260,10 @@ export async function push_search_query(text: string): Promise<boolean>
         {
             return await push_section(result.section).then(_ => true);
         }
+        else if (result.type === 'sections')
+        {
+            return await push_sections(result.sections).then(_ => true);
+        }
         else 
         {
             return false;